@@ -0,0 +1,93 @@
+//! `--bench <n>`: times the scoring functions over `n` synthetic tests, without a
+//! terminal, to catch performance regressions in `scoring`.
+
+use crate::config::{AccuracyMode, FALLBACK_WORDS};
+use crate::scoring;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// Words in each synthetic test.
+const WORDS_PER_TEST: usize = 50;
+// Chance that a typed word has a mistake in it.
+const TYPO_RATE: f64 = 0.2;
+// A fixed seed, so runs are comparable with each other.
+const SEED: u64 = 42;
+
+struct Sample {
+    words: Vec<String>,
+    typed: Vec<String>,
+    word_times: Vec<Option<f64>>,
+}
+
+/// Runs the benchmark and returns the summary lines to print.
+pub fn run(tests: usize) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let samples: Vec<Sample> = (0..tests).map(|_| sample(&mut rng)).collect();
+
+    let timings = [
+        ("char_totals", time(&samples, |s| {
+            black_box(scoring::char_totals(&s.typed, &s.words, s.words.len(), false, &[]));
+        })),
+        ("accuracy", time(&samples, |s| {
+            black_box(scoring::accuracy(AccuracyMode::Character, &s.typed, &s.words, s.words.len(), false, &[]));
+            black_box(scoring::accuracy(AccuracyMode::WordAverage, &s.typed, &s.words, s.words.len(), false, &[]));
+        })),
+        ("optional chars", time(&samples, |s| {
+            black_box(scoring::char_totals(&s.typed, &s.words, s.words.len(), true, &['\'', '-']));
+        })),
+        ("align_word", time(&samples, |s| {
+            for (typed, word) in s.typed.iter().zip(&s.words) {
+                black_box(scoring::align_word(typed, word, false));
+            }
+        })),
+        ("consistency", time(&samples, |s| {
+            black_box(scoring::consistency(&s.words, &s.word_times));
+        })),
+    ];
+
+    let total: Duration = timings.iter().map(|(_, elapsed)| *elapsed).sum();
+    let mut lines = vec![format!("{} synthetic tests of {} words", tests, WORDS_PER_TEST)];
+    for (name, elapsed) in &timings {
+        lines.push(format!("  {:<16}{:>10.2} ms", name, elapsed.as_secs_f64() * 1000.0));
+    }
+    lines.push(format!("  {:<16}{:>10.2} ms", "total", total.as_secs_f64() * 1000.0));
+    lines.push(format!("  {:<16}{:>10.2} µs", "per test", total.as_secs_f64() * 1e6 / tests as f64));
+    lines
+}
+
+fn time(samples: &[Sample], score: impl Fn(&Sample)) -> Duration {
+    let start = Instant::now();
+    for sample in samples {
+        score(sample);
+    }
+    start.elapsed()
+}
+
+/// A test of common words, some typed with a substituted, missing or extra letter.
+fn sample(rng: &mut StdRng) -> Sample {
+    let words: Vec<String> = (0..WORDS_PER_TEST)
+        .map(|_| FALLBACK_WORDS.choose(rng).unwrap().to_string())
+        .collect();
+    let typed = words
+        .iter()
+        .map(|word| {
+            let mut typed: Vec<char> = word.chars().collect();
+            if rng.gen_bool(TYPO_RATE) {
+                let at = rng.gen_range(0..typed.len());
+                match rng.gen_range(0..3) {
+                    0 => typed[at] = 'x',
+                    1 => {
+                        typed.remove(at);
+                    }
+                    _ => typed.insert(at, 'q'),
+                }
+            }
+            typed.into_iter().collect()
+        })
+        .collect();
+    let word_times = words.iter().map(|_| Some(rng.gen_range(0.2..1.5))).collect();
+    Sample { words, typed, word_times }
+}
@@ -0,0 +1,74 @@
+use serde::Deserialize;
+use std::fs;
+use std::io;
+
+/// A fixed passage to type, loaded with `--challenge <path>`, and the score needed to
+/// pass it. At most one of `time_limit` and `word_limit` may be set; with neither, the
+/// whole text is typed.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Challenge {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub text: String,
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+    #[serde(default)]
+    pub word_limit: Option<usize>,
+    pub pass_wpm: f64,
+    #[serde(default)]
+    pub pass_accuracy: Option<f64>,
+}
+
+impl Challenge {
+    pub fn load(path: &str) -> io::Result<Self> {
+        let body = fs::read_to_string(path)?;
+        let challenge: Challenge = serde_json::from_str(&body).map_err(invalid)?;
+        challenge.validate()?;
+        Ok(challenge)
+    }
+
+    fn validate(&self) -> io::Result<()> {
+        if self.text.split_whitespace().next().is_none() {
+            return Err(invalid("\"text\" is empty"));
+        }
+        if self.time_limit.is_some() && self.word_limit.is_some() {
+            return Err(invalid("set either \"time_limit\" or \"word_limit\", not both"));
+        }
+        if self.time_limit == Some(0) || self.word_limit == Some(0) {
+            return Err(invalid("limits must be greater than zero"));
+        }
+        if self.pass_wpm <= 0.0 {
+            return Err(invalid("\"pass_wpm\" must be greater than zero"));
+        }
+        if self.pass_accuracy.is_some_and(|accuracy| !(0.0..=100.0).contains(&accuracy)) {
+            return Err(invalid("\"pass_accuracy\" must be between 0 and 100"));
+        }
+        Ok(())
+    }
+
+    /// The words to type, cut short by `word_limit` if set.
+    pub fn words(&self) -> Vec<String> {
+        self.text
+            .split_whitespace()
+            .take(self.word_limit.unwrap_or(usize::MAX))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn passed(&self, wpm: f64, accuracy: f64) -> bool {
+        wpm >= self.pass_wpm && self.pass_accuracy.is_none_or(|needed| accuracy >= needed)
+    }
+
+    /// What passing takes, e.g. "60 WPM and 95% accuracy".
+    pub fn requirement(&self) -> String {
+        match self.pass_accuracy {
+            Some(accuracy) => format!("{:.0} WPM and {:.0}% accuracy", self.pass_wpm, accuracy),
+            None => format!("{:.0} WPM", self.pass_wpm),
+        }
+    }
+}
+
+fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
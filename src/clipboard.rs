@@ -0,0 +1,46 @@
+use std::io;
+#[cfg(feature = "clipboard")]
+use std::io::Write;
+#[cfg(feature = "clipboard")]
+use std::process::{Command, Stdio};
+
+// Clipboard tools tried in order, with the arguments that make them read stdin.
+#[cfg(feature = "clipboard")]
+const TOOLS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Puts `text` on the system clipboard with the first clipboard tool found on `PATH`.
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> io::Result<()> {
+    for (tool, args) in TOOLS {
+        let Ok(mut child) = Command::new(tool)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound, "no clipboard tool found"))
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "this build doesn't support the clipboard; rebuild with `--features clipboard`",
+    ))
+}
@@ -1,14 +1,56 @@
+use crate::error::{self, TypingTestError};
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use directories::ProjectDirs;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LanguagePack {
     pub name: String,
     pub words: Vec<String>,
+    /// Optional relative frequency of each word, in the same order as `words`.
+    #[serde(default)]
+    pub frequencies: Option<Vec<f64>>,
+    /// The file the pack was loaded from, if it came from the languages directory.
+    #[serde(skip)]
+    pub path: Option<std::path::PathBuf>,
+}
+
+impl LanguagePack {
+    /// Names ending in a difficulty suffix, like `spanish_hard`, would give the same
+    /// results keys as another pack at that difficulty, so such packs aren't loaded.
+    pub fn has_reserved_name(&self) -> bool {
+        [WordListDifficulty::Easy, WordListDifficulty::Hard].into_iter().any(|difficulty| {
+            difficulty
+                .key_suffix()
+                .is_some_and(|suffix| self.name.ends_with(&format!("_{}", suffix)))
+        })
+    }
+}
+
+/// `selected_language` value that draws words from every installed pack combined.
+pub const MIXED_LANGUAGE: &str = "mixed";
+
+/// Time limit that runs a time-mode test with no end until Esc is pressed.
+pub const NO_TIME_LIMIT: u64 = 0;
+
+/// Words used when no language pack or other word list has anything to offer, so a
+/// test can always start.
+pub const FALLBACK_WORDS: &[&str] = &[
+    "the", "of", "and", "to", "in", "is", "you", "that", "it", "he", "was", "for", "on",
+    "are", "as", "with", "his", "they", "at", "be", "this", "have", "from", "or", "one",
+    "had", "by", "word", "but", "not", "what", "all", "were", "we", "when", "your", "can",
+    "said", "there", "use", "an", "each", "which", "she", "do", "how", "their", "if",
+    "will", "up", "other", "about", "out", "many", "then", "them", "these", "so", "some",
+    "her", "would", "make", "like", "him", "into", "time", "has", "look", "two", "more",
+];
+
+pub fn is_known_language(packs: &[LanguagePack], name: &str) -> bool {
+    name == MIXED_LANGUAGE || packs.iter().any(|p| p.name == name)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,6 +59,99 @@ pub enum LayoutTheme {
     Boxes,
 }
 
+/// Which edge of the test screen the live WPM/timer bar sits on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum StatsBarPosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// The key that starts the timer. With `Space` or `Enter`, anything typed before it is ignored.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum StartTrigger {
+    #[default]
+    FirstKey,
+    Space,
+    Enter,
+}
+
+/// Which characters test words may contain, for an easier start.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum Charset {
+    #[default]
+    Any,
+    /// Letters only, without digits, punctuation or symbols.
+    LettersOnly,
+    /// Lowercase letters only.
+    LowercaseOnly,
+}
+
+impl Charset {
+    pub fn allows(self, word: &str) -> bool {
+        match self {
+            Charset::Any => true,
+            Charset::LettersOnly => word.chars().all(char::is_alphabetic),
+            Charset::LowercaseOnly => word.chars().all(|c| c.is_alphabetic() && !c.is_uppercase()),
+        }
+    }
+}
+
+/// How hard the words of a test are, judged by their length.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum WordListDifficulty {
+    /// Short words only, up to EASY_MAX_CHARS characters.
+    Easy,
+    /// Every word in the list.
+    #[default]
+    Medium,
+    /// Long words only, at least HARD_MIN_CHARS characters.
+    Hard,
+}
+
+const EASY_MAX_CHARS: usize = 5;
+const HARD_MIN_CHARS: usize = 6;
+
+impl WordListDifficulty {
+    pub fn allows(self, word: &str) -> bool {
+        let len = word.chars().count();
+        match self {
+            WordListDifficulty::Easy => len <= EASY_MAX_CHARS,
+            WordListDifficulty::Medium => true,
+            WordListDifficulty::Hard => len >= HARD_MIN_CHARS,
+        }
+    }
+
+    /// How the difficulty shows in results keys and labels; Medium, the full list,
+    /// doesn't.
+    fn key_suffix(self) -> Option<&'static str> {
+        match self {
+            WordListDifficulty::Easy => Some("easy"),
+            WordListDifficulty::Medium => None,
+            WordListDifficulty::Hard => Some("hard"),
+        }
+    }
+}
+
+/// Keys for the test controls, by name: "Tab", "Esc", "Space", "Enter" or "F1" to "F12".
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Keybindings {
+    pub restart: String,
+    pub quit: String,
+    pub advance: String,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            restart: "Tab".to_string(),
+            quit: "Esc".to_string(),
+            advance: "Space".to_string(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ColorTheme {
     pub correct: (u8, u8, u8),
@@ -40,14 +175,233 @@ pub enum GameMode {
     Time,
 }
 
+/// Which view the stats screen shows for the selected mode.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub enum StatsView {
+    #[default]
+    Table,
+    Graph,
+    Split,
+    Accuracy,
+}
+
+/// How the accuracy of a test is worked out: over all typed characters, or as the
+/// average of each word's accuracy.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum AccuracyMode {
+    #[default]
+    Character,
+    WordAverage,
+}
+
+/// Order of the mode list on the stats screen, cycled with 'o'.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub enum ModeSort {
+    #[default]
+    Name,
+    Recent,
+    Count,
+    BestWpm,
+}
+
+impl ModeSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Recent,
+            Self::Recent => Self::Count,
+            Self::Count => Self::BestWpm,
+            Self::BestWpm => Self::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Recent => "most recent",
+            Self::Count => "test count",
+            Self::BestWpm => "best WPM",
+        }
+    }
+}
+
+/// Where the stats screen was left, so `-s` reopens there.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StatsPrefs {
+    pub view: StatsView,
+    /// Results key of the selected mode; stored by name since new modes shift the order.
+    pub selected_mode: Option<String>,
+    /// Graph only the latest `graph_recent_count` results.
+    #[serde(default)]
+    pub recent_only: bool,
+    #[serde(default)]
+    pub sort: ModeSort,
+}
+
+/// Test settings used instead of the defaults between `start` and `end` ("HH:MM",
+/// local time). A window may wrap past midnight, e.g. 22:00 to 06:00.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduleEntry {
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub game_mode: Option<GameMode>,
+    #[serde(default)]
+    pub test_length: Option<usize>,
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+}
+
+impl ScheduleEntry {
+    /// Whether `now` falls in this window; entries with unparsable times never match.
+    pub fn contains(&self, now: NaiveTime) -> bool {
+        let parse = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").ok();
+        match (parse(&self.start), parse(&self.end)) {
+            (Some(start), Some(end)) if start <= end => start <= now && now < end,
+            (Some(start), Some(end)) => now >= start || now < end,
+            _ => false,
+        }
+    }
+}
+
+/// One test in a named playlist, run with `--playlist <name>`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlaylistEntry {
+    pub game_mode: GameMode,
+    #[serde(default)]
+    pub test_length: Option<usize>,
+    #[serde(default)]
+    pub time_limit: Option<u64>,
+}
+
+/// The range `--flow` picks each test's settings from. Word counts and time limits are
+/// rounded to multiples of five so results collect under a few modes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FlowBounds {
+    pub game_modes: Vec<GameMode>,
+    pub min_words: usize,
+    pub max_words: usize,
+    pub min_time: u64,
+    pub max_time: u64,
+    pub difficulties: Vec<WordListDifficulty>,
+}
+
+impl Default for FlowBounds {
+    fn default() -> Self {
+        Self {
+            game_modes: vec![GameMode::Words, GameMode::Time],
+            min_words: 10,
+            max_words: 50,
+            min_time: 15,
+            max_time: 60,
+            difficulties: vec![WordListDifficulty::Easy, WordListDifficulty::Medium, WordListDifficulty::Hard],
+        }
+    }
+}
+
+/// What kind of test a result came from. The results key is built from it, so the
+/// stats screen can describe a mode without picking the key apart.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModeInfo {
+    pub game_mode: GameMode,
+    /// Word count in words mode, seconds in time mode.
+    pub amount: u64,
+    pub language: String,
+    /// Results saved before difficulty was stored were all Medium.
+    #[serde(default)]
+    pub difficulty: WordListDifficulty,
+}
+
+impl ModeInfo {
+    /// e.g. `words_25_english`, with `_easy` or `_hard` after it for those difficulties.
+    /// Medium keeps the plain key, so keys saved before difficulty was part of them
+    /// still name the same results.
+    pub fn key(&self) -> String {
+        let key = match self.game_mode {
+            GameMode::Words => format!("words_{}_{}", self.amount, self.language),
+            GameMode::Time => format!("time_{}_{}", self.amount, self.language),
+        };
+        match self.difficulty.key_suffix() {
+            Some(suffix) => format!("{}_{}", key, suffix),
+            None => key,
+        }
+    }
+
+    /// Reads a key made by `key`, for results saved before the metadata was stored.
+    pub fn from_key(key: &str) -> Option<Self> {
+        let mut parts = key.splitn(3, '_');
+        let game_mode = match parts.next()? {
+            "words" => GameMode::Words,
+            "time" => GameMode::Time,
+            _ => return None,
+        };
+        let amount = parts.next()?.parse().ok()?;
+        let rest = parts.next()?;
+        let (language, difficulty) = [WordListDifficulty::Easy, WordListDifficulty::Hard]
+            .into_iter()
+            .find_map(|difficulty| {
+                let language = rest.strip_suffix(&format!("_{}", difficulty.key_suffix()?))?;
+                Some((language, difficulty))
+            })
+            .unwrap_or((rest, WordListDifficulty::Medium));
+        Some(Self { game_mode, amount, language: language.to_string(), difficulty })
+    }
+
+    /// e.g. "words 25 english".
+    pub fn label(&self) -> String {
+        let mode = match self.game_mode {
+            GameMode::Words => "words",
+            GameMode::Time => "time",
+        };
+        let label = match self.game_mode {
+            GameMode::Time if self.amount == NO_TIME_LIMIT => format!("{} endless {}", mode, self.language),
+            _ => format!("{} {} {}", mode, self.amount, self.language),
+        };
+        match self.difficulty.key_suffix() {
+            Some(suffix) => format!("{} {}", label, suffix),
+            None => label,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TestResult {
     pub wpm: f64,
     pub accuracy: f64,
+    /// Longest run of consecutive correct words.
+    #[serde(default)]
+    pub max_combo: u32,
+    /// How `accuracy` was computed; older results are per character.
+    #[serde(default)]
+    pub accuracy_mode: AccuracyMode,
+    /// Filled in from the results key on load for older results.
+    #[serde(default)]
+    pub mode: Option<ModeInfo>,
+    /// Seed the test's words were drawn with, so it can be replayed; missing on older results.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The rest of what decided the words, so a replay can tell whether the seed still
+    /// gives the same ones; missing on older results.
+    #[serde(default)]
+    pub word_settings: Option<WordSettings>,
     pub timestamp: String,
 }
 
+/// The settings and word list a test's words were drawn from, besides its mode and seed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WordSettings {
+    pub restrict_charset: Charset,
+    pub frequency_weighted: bool,
+    pub split_on_hyphen: bool,
+    /// `words::fingerprint` of the word list and frequencies, before splitting or
+    /// filtering.
+    pub list_fingerprint: u64,
+}
+
+// Fields missing from the file take their value from `Config::default()`, so a config
+// written by an older version still loads instead of being replaced.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct Config {
     pub default_test_length: usize,
     pub default_time_limit: u64,
@@ -58,6 +412,109 @@ pub struct Config {
     pub results: HashMap<String, Vec<TestResult>>,
     pub language_packs: Vec<LanguagePack>,
     pub selected_language: String,
+    pub weak_words: HashMap<String, u32>,
+    pub ui_fg: Option<(u8, u8, u8)>,
+    pub ui_bg: Option<(u8, u8, u8)>,
+    pub case_insensitive: bool,
+    #[serde(default = "default_graph_height")]
+    pub graph_height: u16,
+    pub random_layout: bool,
+    pub enter_advances: bool,
+    pub live_wpm_smoothing: f64,
+    pub show_kpm: bool,
+    pub caret_blink: bool,
+    pub max_corrections_per_word: Option<u32>,
+    pub frequency_weighted: bool,
+    pub target_wpm: Option<f64>,
+    pub stats_prefs: StatsPrefs,
+    pub graph_clip_percentile: Option<f64>,
+    /// A missing field is a config from before the limit, which gets it too; `null` in
+    /// the file turns it off.
+    #[serde(default = "default_max_saved_wpm")]
+    pub max_saved_wpm: Option<f64>,
+    pub focus_mode: bool,
+    pub auto_next_secs: Option<u64>,
+    pub optional_chars: Vec<char>,
+    pub schedule: Vec<ScheduleEntry>,
+    pub ghost_pace: bool,
+    pub auto_advance_on_correct: bool,
+    pub allow_paste: bool,
+    #[serde(default = "default_graph_recent_count")]
+    pub graph_recent_count: usize,
+    pub playlists: HashMap<String, Vec<PlaylistEntry>>,
+    pub flow: FlowBounds,
+    #[serde(default = "default_graph_min_samples")]
+    pub graph_min_samples: usize,
+    pub live_wpm_integer: bool,
+    /// Results key listed first on the stats screen.
+    pub pinned_mode: Option<String>,
+    /// Colors for the mistakes on the results screen; `None` uses `color_theme`.
+    pub review_color_theme: Option<ColorTheme>,
+    pub accuracy_mode: AccuracyMode,
+    pub pause_on_blur: bool,
+    /// A missing field is a config from before the tutorial existed, whose owner doesn't
+    /// need it, so this defaults to true while `Default` (a new install) has false.
+    #[serde(default = "default_tutorial_shown")]
+    pub tutorial_shown: bool,
+    /// Opens the settings menu before the very first test, along with the tutorial.
+    #[serde(default = "default_prompt_menu_on_first_run")]
+    pub prompt_menu_on_first_run: bool,
+    pub max_line_width: Option<u16>,
+    pub daily_best_only: bool,
+    pub beep_on_error: bool,
+    pub chime_on_complete: bool,
+    pub stats_bar_position: StatsBarPosition,
+    pub hide_upcoming_words: bool,
+    pub start_trigger: StartTrigger,
+    pub show_recent_average: bool,
+    pub split_on_hyphen: bool,
+    /// Weight of the newest result in the stats screen's recent-weighted average; `None` hides it.
+    pub recent_weighted_alpha: Option<f64>,
+    pub first_key_grace_ms: u64,
+    pub restrict_charset: Charset,
+    pub precise_timer: bool,
+    pub word_list_difficulty: WordListDifficulty,
+    pub keybindings: Keybindings,
+    #[serde(default = "default_decimal_separator")]
+    pub decimal_separator: char,
+    /// Detected from the environment on every load, never saved.
+    #[serde(skip, default = "color_supported")]
+    pub use_color: bool,
+}
+
+fn default_graph_height() -> u16 {
+    10
+}
+
+fn default_graph_recent_count() -> usize {
+    30
+}
+
+fn default_graph_min_samples() -> usize {
+    3
+}
+
+fn default_tutorial_shown() -> bool {
+    true
+}
+
+fn default_decimal_separator() -> char {
+    '.'
+}
+
+fn default_prompt_menu_on_first_run() -> bool {
+    true
+}
+
+/// False when `NO_COLOR` is set (https://no-color.org/) or the terminal is `dumb`.
+pub fn color_supported() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    !no_color && !dumb
+}
+
+fn default_max_saved_wpm() -> Option<f64> {
+    Some(300.0)
 }
 
 impl Default for Config {
@@ -72,20 +529,130 @@ impl Default for Config {
             results: HashMap::new(),
             language_packs: Vec::new(), // Will be populated by load_config
             selected_language: "english".to_string(), // Will be validated by load_config
+            weak_words: HashMap::new(),
+            ui_fg: None, // Terminal default
+            ui_bg: None, // Terminal default
+            case_insensitive: false,
+            graph_height: default_graph_height(),
+            random_layout: false,
+            enter_advances: false,
+            live_wpm_smoothing: 0.0, // No smoothing
+            show_kpm: false,
+            caret_blink: false,
+            max_corrections_per_word: None, // Unlimited
+            frequency_weighted: false,
+            target_wpm: None,
+            stats_prefs: StatsPrefs::default(),
+            graph_clip_percentile: None, // Scale to the highest result
+            max_saved_wpm: default_max_saved_wpm(),
+            focus_mode: false,
+            auto_next_secs: None, // Wait for Tab
+            optional_chars: Vec::new(),
+            schedule: Vec::new(),
+            ghost_pace: false,
+            auto_advance_on_correct: false,
+            allow_paste: false, // Pasting would fake a high WPM
+            graph_recent_count: default_graph_recent_count(),
+            playlists: HashMap::new(),
+            flow: FlowBounds::default(),
+            graph_min_samples: default_graph_min_samples(),
+            live_wpm_integer: false,
+            pinned_mode: None,
+            review_color_theme: None, // Same as the test
+            accuracy_mode: AccuracyMode::Character,
+            pause_on_blur: false,
+            tutorial_shown: false, // Shown once on the first run; see the field
+            prompt_menu_on_first_run: true,
+            max_line_width: None, // Use the terminal width
+            daily_best_only: false,
+            beep_on_error: false,
+            chime_on_complete: false,
+            stats_bar_position: StatsBarPosition::Top,
+            hide_upcoming_words: false,
+            start_trigger: StartTrigger::FirstKey,
+            show_recent_average: false,
+            split_on_hyphen: false,
+            recent_weighted_alpha: None,
+            first_key_grace_ms: 0,
+            restrict_charset: Charset::Any,
+            precise_timer: false,
+            word_list_difficulty: WordListDifficulty::Medium,
+            keybindings: Keybindings::default(),
+            decimal_separator: default_decimal_separator(),
+            use_color: color_supported(),
         }
     }
 }
 
-fn get_config_path() -> Option<PathBuf> {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "gemini", "typing_test") {
-        let config_dir = proj_dirs.config_dir();
-        if !config_dir.exists() {
-            fs::create_dir_all(config_dir).ok()?;
-        }
-        Some(config_dir.join("config.json"))
-    } else {
-        None
+/// Environment variable that sets the config file path, taking precedence over the
+/// platform config directory.
+pub const CONFIG_ENV_VAR: &str = "TYPING_TEST_CONFIG";
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Selects a named profile for this run; must be called before the config is loaded.
+/// Each profile keeps its own settings and results in a separate file.
+pub fn set_profile(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "invalid profile name '{}': use letters, digits, '-' or '_'",
+            name
+        ));
     }
+    PROFILE
+        .set(name.to_string())
+        .map_err(|_| "the profile can only be set once".to_string())
+}
+
+/// Turns `config.json` into `config_<profile>.json` when a profile is active.
+fn with_profile(path: PathBuf) -> PathBuf {
+    let Some(profile) = PROFILE.get() else {
+        return path;
+    };
+    let stem = path.file_stem().map_or("config".into(), |s| s.to_string_lossy());
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}_{}.{}", stem, profile, ext.to_string_lossy()),
+        None => format!("{}_{}", stem, profile),
+    };
+    path.with_file_name(file_name)
+}
+
+fn env_config_path() -> Option<Result<PathBuf, String>> {
+    let path = PathBuf::from(std::env::var_os(CONFIG_ENV_VAR)?);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty())
+        && !parent.exists()
+        && let Err(e) = fs::create_dir_all(parent)
+    {
+        return Some(Err(format!("could not create {}: {}", parent.display(), e)));
+    }
+    Some(Ok(path))
+}
+
+fn project_config_path() -> Result<PathBuf, String> {
+    let proj_dirs = ProjectDirs::from("com", "gemini", "typing_test")
+        .ok_or_else(|| "no home directory could be determined".to_string())?;
+    let config_dir = proj_dirs.config_dir();
+    if !config_dir.exists() {
+        fs::create_dir_all(config_dir)
+            .map_err(|e| format!("could not create {}: {}", config_dir.display(), e))?;
+    }
+    Ok(config_dir.join("config.json"))
+}
+
+/// Where the config file lives, or why there is nowhere to keep it.
+/// `TYPING_TEST_CONFIG` wins over the platform config directory.
+pub fn config_path() -> Result<PathBuf, String> {
+    env_config_path()
+        .unwrap_or_else(project_config_path)
+        .map(with_profile)
+}
+
+pub fn get_config_dir() -> Option<PathBuf> {
+    config_path().ok()?.parent().map(|dir| dir.to_path_buf())
+}
+
+fn get_config_path() -> Option<PathBuf> {
+    config_path().ok()
 }
 
 fn log_debug(message: &str) {
@@ -110,24 +677,24 @@ pub fn load_language_packs() -> std::io::Result<Vec<LanguagePack>> {
     let paths = fs::read_dir(&language_dir)?;
     for path in paths {
         let path = path?.path();
-        if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext == "json" {
-                    log_debug(&format!("Found language file: {:?}", path));
-                    if let Ok(file_content) = fs::read_to_string(&path) {
-                        match serde_json::from_str::<LanguagePack>(&file_content) {
-                            Ok(pack) => {
-                                log_debug(&format!("Successfully parsed language pack: {}", pack.name));
-                                packs.push(pack);
-                            }
-                            Err(e) => {
-                                log_debug(&format!("Failed to parse {:?}: {}", path, e));
-                            }
-                        }
-                    } else {
-                        log_debug(&format!("Failed to read file: {:?}", path));
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            log_debug(&format!("Found language file: {:?}", path));
+            if let Ok(file_content) = fs::read_to_string(&path) {
+                match serde_json::from_str::<LanguagePack>(&file_content) {
+                    Ok(pack) if pack.has_reserved_name() => {
+                        log_debug(&format!("Skipping {:?}: pack names can't end in _easy or _hard", path));
+                    }
+                    Ok(mut pack) => {
+                        log_debug(&format!("Successfully parsed language pack: {}", pack.name));
+                        pack.path = Some(path.clone());
+                        packs.push(pack);
+                    }
+                    Err(e) => {
+                        log_debug(&format!("Failed to parse {:?}: {}", path, e));
                     }
                 }
+            } else {
+                log_debug(&format!("Failed to read file: {:?}", path));
             }
         }
     }
@@ -135,7 +702,29 @@ pub fn load_language_packs() -> std::io::Result<Vec<LanguagePack>> {
     Ok(packs)
 }
 
-pub fn load_config() -> Config {
+/// Deletes the file of the pack named `name` from the languages directory and drops the
+/// pack from `config`, switching to the first remaining pack if it was selected.
+pub fn delete_language_pack(config: &mut Config, name: &str) -> std::io::Result<()> {
+    let Some(index) = config.language_packs.iter().position(|p| p.name == name) else {
+        return Ok(());
+    };
+    if let Some(path) = &config.language_packs[index].path {
+        fs::remove_file(path)?;
+    }
+    config.language_packs.remove(index);
+    if config.selected_language == name {
+        config.selected_language = config
+            .language_packs
+            .first()
+            .map_or_else(|| "english".to_string(), |pack| pack.name.clone());
+    }
+    Ok(())
+}
+
+/// Loads the config and the installed language packs. With `save`, a missing or invalid
+/// file is replaced with the defaults and missing settings are filled in on disk;
+/// without it, as under `--no-save`, nothing is written.
+pub fn load_config(save: bool) -> Config {
     let current_language_packs = load_language_packs().unwrap_or_default();
     let default_selected_language = if current_language_packs.is_empty() {
         "english".to_string()
@@ -148,17 +737,22 @@ pub fn load_config() -> Config {
             match serde_json::from_str::<Config>(&config_str) {
                 Ok(mut c) => {
                     c.language_packs = current_language_packs;
-                    if !c.language_packs.iter().any(|p| p.name == c.selected_language) {
+                    if !is_known_language(&c.language_packs, &c.selected_language) {
                         c.selected_language = default_selected_language.clone();
                     }
+                    if save {
+                        fill_missing_fields(&config_path, &config_str, &c);
+                    }
                     c
                 },
                 Err(_) => {
                     // If the file is invalid, create a default one
-                    let mut new_config = Config::default();
-                    new_config.language_packs = current_language_packs;
-                    new_config.selected_language = default_selected_language.clone();
-                    if let Ok(config_str) = serde_json::to_string_pretty(&new_config) {
+                    let new_config = Config {
+                        language_packs: current_language_packs,
+                        selected_language: default_selected_language.clone(),
+                        ..Config::default()
+                    };
+                    if save && let Ok(config_str) = serde_json::to_string_pretty(&new_config) {
                         fs::write(config_path, config_str).ok();
                     }
                     new_config
@@ -166,39 +760,196 @@ pub fn load_config() -> Config {
             }
         } else {
             // If the file doesn't exist, create a default one
-            let mut new_config = Config::default();
-            new_config.language_packs = current_language_packs;
-            new_config.selected_language = default_selected_language.clone();
-            if let Ok(config_str) = serde_json::to_string_pretty(&new_config) {
+            let new_config = Config {
+                language_packs: current_language_packs,
+                selected_language: default_selected_language.clone(),
+                ..Config::default()
+            };
+            if save && let Ok(config_str) = serde_json::to_string_pretty(&new_config) {
                 fs::write(config_path, config_str).ok();
             }
             new_config
         }
     } else {
         // If config path cannot be determined, return a default config
-        let mut new_config = Config::default();
-        new_config.language_packs = current_language_packs;
-        new_config.selected_language = default_selected_language.clone();
-        new_config
+        Config {
+            language_packs: current_language_packs,
+            selected_language: default_selected_language.clone(),
+            ..Config::default()
+        }
     };
 
-    // Ensure language_packs are always up-to-date in the returned config
-    config.language_packs = load_language_packs().unwrap_or_default();
-    if !config.language_packs.iter().any(|p| p.name == config.selected_language) {
+    if !is_known_language(&config.language_packs, &config.selected_language) {
         config.selected_language = default_selected_language;
     }
+    fill_mode_info(&mut config);
 
     config
 }
 
-pub fn save_config(config: &Config) -> std::io::Result<()> {
-    if let Some(config_path) = get_config_path() {
-        let config_str = serde_json::to_string_pretty(config)?;
-        fs::write(config_path, config_str)
-    } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Could not find config directory",
-        ))
+/// Rewrites the config file if it lacks any setting this version has, so the file
+/// always lists every option with its current value.
+fn fill_missing_fields(path: &Path, on_disk: &str, config: &Config) {
+    let (Ok(on_disk), Ok(current)) = (
+        serde_json::from_str::<serde_json::Value>(on_disk),
+        serde_json::to_value(config),
+    ) else {
+        return;
+    };
+    if has_missing_keys(&on_disk, &current)
+        && let Ok(config_str) = serde_json::to_string_pretty(&current)
+    {
+        fs::write(path, config_str).ok();
+    }
+}
+
+/// Whether `current` has an object key, at any depth, that `on_disk` doesn't.
+fn has_missing_keys(on_disk: &serde_json::Value, current: &serde_json::Value) -> bool {
+    match (on_disk, current) {
+        (serde_json::Value::Object(on_disk), serde_json::Value::Object(current)) => {
+            current.iter().any(|(key, value)| match on_disk.get(key) {
+                Some(existing) => has_missing_keys(existing, value),
+                None => true,
+            })
+        }
+        _ => false,
     }
-}
\ No newline at end of file
+}
+
+/// Gives results saved before `TestResult::mode` existed the metadata their key describes.
+/// Keys from before difficulty was stored have no suffix, so their results read as
+/// Medium, the only difficulty there was.
+fn fill_mode_info(config: &mut Config) {
+    migrate_keys_without_language(config);
+    for (key, results) in config.results.iter_mut() {
+        let Some(info) = ModeInfo::from_key(key) else {
+            continue;
+        };
+        for result in results.iter_mut().filter(|result| result.mode.is_none()) {
+            result.mode = Some(info.clone());
+        }
+    }
+}
+
+/// Moves results from keys made before the language was part of them (`words_20`) to
+/// the English key, merged in timestamp order with any results already there.
+fn migrate_keys_without_language(config: &mut Config) {
+    let old_keys: Vec<String> = config
+        .results
+        .keys()
+        .filter(|key| key.split('_').count() == 2 && ModeInfo::from_key(&format!("{}_english", key)).is_some())
+        .cloned()
+        .collect();
+    for old_key in old_keys {
+        merge_modes(config, &old_key, &format!("{}_english", old_key)).ok();
+    }
+}
+
+/// Moves every result saved under `from` into `into`, in timestamp order. `into` is
+/// created if needed, so this also renames a mode. Returns how many results moved.
+pub fn merge_modes(config: &mut Config, from: &str, into: &str) -> Result<usize, String> {
+    if from == into {
+        return Err("can't merge a mode into itself".to_string());
+    }
+    let moved = config
+        .results
+        .remove(from)
+        .ok_or_else(|| format!("no results saved under '{}'", from))?;
+    let count = moved.len();
+    let target_info = ModeInfo::from_key(into);
+    let merged = config.results.entry(into.to_string()).or_default();
+    merged.extend(moved.into_iter().map(|result| TestResult {
+        mode: target_info.clone().or(result.mode),
+        ..result
+    }));
+    merged.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    for key in [&mut config.pinned_mode, &mut config.stats_prefs.selected_mode] {
+        if key.as_deref() == Some(from) {
+            *key = Some(into.to_string());
+        }
+    }
+    Ok(count)
+}
+
+/// Reads the saved config without scanning the language pack directory or writing
+/// anything, for commands that only need settings and results. `language_packs` is
+/// left empty and `selected_language` is not validated.
+pub fn load_config_light() -> Config {
+    get_config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|config_str| serde_json::from_str::<Config>(&config_str).ok())
+        .map(|config| Config { language_packs: Vec::new(), ..config })
+        .map(|mut config| {
+            fill_mode_info(&mut config);
+            config
+        })
+        .unwrap_or_default()
+}
+
+pub fn save_config(config: &Config) -> error::Result<()> {
+    let config_path = config_path().map_err(TypingTestError::NoConfigDir)?;
+    let config_str = serde_json::to_string_pretty(config)?;
+    fs::write(config_path, config_str)?;
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(difficulty: WordListDifficulty) -> ModeInfo {
+        ModeInfo { game_mode: GameMode::Words, amount: 25, language: "english".to_string(), difficulty }
+    }
+
+    #[test]
+    fn difficulty_is_part_of_the_key_except_for_medium() {
+        assert_eq!(mode(WordListDifficulty::Medium).key(), "words_25_english");
+        assert_eq!(mode(WordListDifficulty::Easy).key(), "words_25_english_easy");
+        assert_eq!(mode(WordListDifficulty::Hard).key(), "words_25_english_hard");
+        assert_eq!(mode(WordListDifficulty::Hard).label(), "words 25 english hard");
+    }
+
+    #[test]
+    fn from_key_reads_the_difficulty_back() {
+        for difficulty in [WordListDifficulty::Easy, WordListDifficulty::Medium, WordListDifficulty::Hard] {
+            let info = ModeInfo::from_key(&mode(difficulty).key()).unwrap();
+            assert_eq!(info.difficulty, difficulty);
+            assert_eq!(info.language, "english");
+        }
+        let mixed = ModeInfo::from_key("time_30_mixed_hard").unwrap();
+        assert_eq!((mixed.amount, mixed.language.as_str(), mixed.difficulty), (30, "mixed", WordListDifficulty::Hard));
+    }
+
+    #[test]
+    fn pack_names_that_look_like_a_difficulty_are_reserved() {
+        let pack = |name: &str| LanguagePack { name: name.to_string(), words: Vec::new(), frequencies: None, path: None };
+        // `words_25_spanish_hard` can only mean spanish at Hard, so a pack called
+        // `spanish_hard` would have its results read as another pack's.
+        let key = ModeInfo { language: "spanish".to_string(), ..mode(WordListDifficulty::Hard) }.key();
+        assert_eq!(key, "words_25_spanish_hard");
+        assert_eq!(ModeInfo::from_key(&key).unwrap().language, "spanish");
+        assert!(pack("spanish_hard").has_reserved_name());
+        assert!(pack("spanish_easy").has_reserved_name());
+        assert!(!pack("spanish").has_reserved_name());
+        assert!(!pack("hard").has_reserved_name());
+        assert!(!pack("spanish_medium").has_reserved_name());
+    }
+
+    #[test]
+    fn results_saved_before_difficulty_read_as_medium() {
+        let old: ModeInfo = serde_json::from_str(r#"{"game_mode":"Words","amount":25,"language":"english"}"#).unwrap();
+        assert_eq!(old.difficulty, WordListDifficulty::Medium);
+        assert_eq!(old.key(), "words_25_english");
+    }
+
+    #[test]
+    fn missing_fields_load_as_an_existing_user() {
+        let old: Config = serde_json::from_str("{}").unwrap();
+        assert!(old.tutorial_shown);
+        assert_eq!(old.max_saved_wpm, Some(300.0));
+        assert_eq!(old.graph_height, Config::default().graph_height);
+        assert!(!Config::default().tutorial_shown);
+
+        let unlimited: Config = serde_json::from_str(r#"{"max_saved_wpm":null}"#).unwrap();
+        assert_eq!(unlimited.max_saved_wpm, None);
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use std::io;
+
+/// Everything that can stop the application, kept apart so the message says what to fix.
+pub enum TypingTestError {
+    Io(io::Error),
+    /// The config couldn't be turned into or read from JSON.
+    Config(serde_json::Error),
+    /// Setting up or restoring the terminal failed.
+    Terminal(io::Error),
+    /// There is nowhere to keep the config file, and why.
+    NoConfigDir(String),
+}
+
+pub type Result<T> = std::result::Result<T, TypingTestError>;
+
+impl fmt::Display for TypingTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Config(e) => write!(f, "invalid config: {}", e),
+            Self::Terminal(e) => write!(f, "terminal error: {}", e),
+            Self::NoConfigDir(reason) => write!(f, "no config directory: {}", reason),
+        }
+    }
+}
+
+// `main` reports a returned error with `Debug`, so show the readable message there too.
+impl fmt::Debug for TypingTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for TypingTestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) | Self::Terminal(e) => Some(e),
+            Self::Config(e) => Some(e),
+            Self::NoConfigDir(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for TypingTestError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TypingTestError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Config(e)
+    }
+}
@@ -0,0 +1,168 @@
+//! A back buffer for the test screen. Each frame is drawn into a grid of cells, and
+//! only the cells that differ from the previous frame are written to the terminal, so
+//! the screen never has to be cleared between frames and doesn't flicker.
+
+use crate::config::Config;
+use crate::graphemes::graphemes;
+use crate::ui;
+use crossterm::{
+    cursor,
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+use std::io::{self, Stdout, Write};
+
+/// How a cell is drawn. `fg: None` is the UI foreground from the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub underlined: bool,
+}
+
+impl Style {
+    pub fn fg(color: Color) -> Self {
+        Self { fg: Some(color), underlined: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    /// One grapheme cluster, which the layout counts as one column.
+    symbol: String,
+    style: Style,
+}
+
+impl Cell {
+    fn blank() -> Self {
+        Self { symbol: " ".to_string(), style: Style::default() }
+    }
+}
+
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    /// A blank frame the size of the terminal.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self { width, height, cells: vec![Cell::blank(); width as usize * height as usize] }
+    }
+
+    /// Puts `text` in the cells from `(x, y)` rightwards, one grapheme per cell. Anything
+    /// past the right edge or below the bottom is cut off.
+    pub fn print(&mut self, x: u16, y: u16, text: &str, style: Style) {
+        if y >= self.height {
+            return;
+        }
+        for (i, symbol) in graphemes(text).into_iter().enumerate() {
+            let column = x as usize + i;
+            if column >= self.width as usize {
+                break;
+            }
+            self.cells[y as usize * self.width as usize + column] = Cell { symbol: symbol.to_string(), style };
+        }
+    }
+
+    /// Writes the cells that differ from `previous` to the terminal, moving the cursor
+    /// only where the changed cells aren't side by side. Without a previous frame of the
+    /// same size the screen is cleared and everything is drawn.
+    pub fn render(&self, stdout: &mut Stdout, config: &Config, previous: Option<&Frame>) -> io::Result<()> {
+        let previous = self.comparable(previous);
+        ui::queue_ui_colors(stdout, config)?;
+        if previous.is_none() {
+            stdout.queue(Clear(ClearType::All))?;
+        }
+
+        let mut style = Style::default();
+        let mut next_position = None;
+        for (position, cell) in self.changes(previous) {
+            if next_position != Some(position) {
+                stdout.queue(cursor::MoveTo(position.0, position.1))?;
+            }
+            if cell.style != style {
+                apply_style(stdout, config, cell.style)?;
+                style = cell.style;
+            }
+            stdout.queue(Print(&cell.symbol))?;
+            // Wide characters may take up two columns, so the cursor is moved explicitly
+            // after anything that isn't plain ASCII.
+            next_position = cell.symbol.is_ascii().then_some((position.0 + 1, position.1));
+        }
+        apply_style(stdout, config, Style::default())?;
+        stdout.flush()
+    }
+
+    /// `previous` if it can be diffed against, or `None` when the screen has to be
+    /// redrawn in full because there is no previous frame or the terminal was resized.
+    fn comparable<'a>(&self, previous: Option<&'a Frame>) -> Option<&'a Frame> {
+        previous.filter(|p| p.width == self.width && p.height == self.height)
+    }
+
+    /// The cells that differ from `previous`, or from a cleared screen without one, with
+    /// their positions in drawing order.
+    fn changes<'a>(&'a self, previous: Option<&'a Frame>) -> impl Iterator<Item = ((u16, u16), &'a Cell)> {
+        let width = self.width as usize;
+        let blank = Cell::blank();
+        self.cells.iter().enumerate().filter_map(move |(i, cell)| {
+            let changed = *cell != *previous.map_or(&blank, |p| &p.cells[i]);
+            changed.then_some((((i % width) as u16, (i / width) as u16), cell))
+        })
+    }
+}
+
+fn apply_style(stdout: &mut Stdout, config: &Config, style: Style) -> io::Result<()> {
+    match style.fg {
+        Some(color) if config.use_color => {
+            stdout.queue(SetForegroundColor(color))?;
+        }
+        _ => ui::queue_ui_colors(stdout, config)?,
+    }
+    stdout.queue(SetAttribute(if style.underlined { Attribute::Underlined } else { Attribute::NoUnderline }))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(frame: &Frame, previous: Option<&Frame>) -> Vec<((u16, u16), String)> {
+        frame
+            .changes(frame.comparable(previous))
+            .map(|(position, cell)| (position, cell.symbol.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn only_changed_cells_are_emitted() {
+        let mut first = Frame::new(10, 3);
+        first.print(0, 0, "hello", Style::default());
+        first.print(2, 2, "ab", Style::default());
+        let mut second = Frame::new(10, 3);
+        second.print(0, 0, "help", Style::default());
+        second.print(2, 2, "ab", Style::fg(Color::Red));
+
+        assert_eq!(
+            changed(&second, Some(&first)),
+            vec![((3, 0), "p".to_string()), ((4, 0), " ".to_string()), ((2, 2), "a".to_string()), ((3, 2), "b".to_string())]
+        );
+        assert!(changed(&second, Some(&second)).is_empty());
+    }
+
+    #[test]
+    fn a_resize_redraws_everything() {
+        let mut previous = Frame::new(10, 3);
+        previous.print(0, 0, "ab", Style::default());
+        let mut frame = Frame::new(12, 3);
+        frame.print(0, 0, "ab", Style::default());
+        frame.print(11, 2, "é", Style::default());
+
+        assert!(frame.comparable(Some(&previous)).is_none());
+        assert_eq!(
+            changed(&frame, Some(&previous)),
+            vec![((0, 0), "a".to_string()), ((1, 0), "b".to_string()), ((11, 2), "é".to_string())]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! A small approximation of Unicode extended grapheme clusters, so that emoji and
+//! accented letters built from combining marks are compared and drawn as one
+//! character. It covers combining marks, variation selectors, emoji modifiers, ZWJ
+//! sequences, flags, Hangul syllables and CRLF, which is what word lists use in
+//! practice; it is not a full implementation of UAX #29.
+
+/// Splits `s` into grapheme clusters.
+///
+/// Known differences from UAX #29: only the marks listed in `extends_cluster` attach
+/// (other spacing marks and prepended characters, as in most Indic scripts besides
+/// Devanagari, stay separate), and anything after a zero width joiner is attached,
+/// not only emoji.
+pub fn graphemes(s: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    let mut regional_run = 0;
+
+    for (i, c) in s.char_indices() {
+        let joins = match prev {
+            None => false,
+            Some('\r') => c == '\n',
+            Some(p) if p.is_control() || c.is_control() => false,
+            Some(p) => {
+                extends_cluster(c)
+                    || p == ZWJ
+                    || (is_regional_indicator(p) && is_regional_indicator(c) && regional_run % 2 == 1)
+                    || hangul_joins(p, c)
+            }
+        };
+        if !joins && i > 0 {
+            clusters.push(&s[start..i]);
+            start = i;
+        }
+        regional_run = if is_regional_indicator(c) { regional_run + 1 } else { 0 };
+        prev = Some(c);
+    }
+    if start < s.len() {
+        clusters.push(&s[start..]);
+    }
+    clusters
+}
+
+/// Number of grapheme clusters in `s`, used as its on-screen length.
+pub fn count(s: &str) -> usize {
+    graphemes(s).len()
+}
+
+/// Byte offset at which grapheme cluster `index` of `s` starts, or the end of `s`.
+fn byte_offset(s: &str, index: usize) -> usize {
+    graphemes(s).iter().take(index).map(|cluster| cluster.len()).sum()
+}
+
+/// Inserts `c` before grapheme cluster `index` of `s`, or at the end past the last one.
+pub fn insert(s: &mut String, index: usize, c: char) {
+    let at = byte_offset(s, index);
+    s.insert(at, c);
+}
+
+/// Removes grapheme cluster `index` from `s`, returning whether there was one.
+pub fn remove(s: &mut String, index: usize) -> bool {
+    let Some(len) = graphemes(s).get(index).map(|cluster| cluster.len()) else {
+        return false;
+    };
+    let start = byte_offset(s, index);
+    s.replace_range(start..start + len, "");
+    true
+}
+
+/// The first `n` grapheme clusters of `s`.
+pub fn prefix(s: &str, n: usize) -> &str {
+    &s[..byte_offset(s, n)]
+}
+
+const ZWJ: char = '\u{200D}';
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+/// Whether two Hangul characters belong to the same syllable: leading consonants join
+/// anything that can follow them, vowels join vowels and trailing consonants, and
+/// trailing consonants join each other.
+fn hangul_joins(prev: char, c: char) -> bool {
+    matches!(
+        (hangul_kind(prev), hangul_kind(c)),
+        (Some(Hangul::Leading), Some(Hangul::Leading | Hangul::Vowel | Hangul::Lv | Hangul::Lvt))
+            | (Some(Hangul::Lv | Hangul::Vowel), Some(Hangul::Vowel | Hangul::Trailing))
+            | (Some(Hangul::Lvt | Hangul::Trailing), Some(Hangul::Trailing))
+    )
+}
+
+#[derive(Clone, Copy)]
+enum Hangul {
+    Leading,
+    Vowel,
+    Trailing,
+    /// A precomposed syllable without a trailing consonant.
+    Lv,
+    /// A precomposed syllable with a trailing consonant.
+    Lvt,
+}
+
+fn hangul_kind(c: char) -> Option<Hangul> {
+    match c {
+        '\u{1100}'..='\u{115F}' | '\u{A960}'..='\u{A97C}' => Some(Hangul::Leading),
+        '\u{1160}'..='\u{11A7}' | '\u{D7B0}'..='\u{D7C6}' => Some(Hangul::Vowel),
+        '\u{11A8}'..='\u{11FF}' | '\u{D7CB}'..='\u{D7FB}' => Some(Hangul::Trailing),
+        '\u{AC00}'..='\u{D7A3}' if (c as u32 - 0xAC00).is_multiple_of(28) => Some(Hangul::Lv),
+        '\u{AC00}'..='\u{D7A3}' => Some(Hangul::Lvt),
+        _ => None,
+    }
+}
+
+/// Characters that attach to the one before them.
+fn extends_cluster(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'     // Combining diacritical marks
+        | '\u{0483}'..='\u{0489}'   // Cyrillic combining marks
+        | '\u{0591}'..='\u{05BD}'   // Hebrew points
+        | '\u{064B}'..='\u{065F}'   // Arabic vowel marks
+        | '\u{0900}'..='\u{0903}'   // Devanagari signs
+        | '\u{093A}'..='\u{094F}'   // Devanagari vowel signs and virama
+        | '\u{1AB0}'..='\u{1AFF}'   // Combining diacritical marks extended
+        | '\u{1DC0}'..='\u{1DFF}'   // Combining diacritical marks supplement
+        | '\u{200D}'                // Zero width joiner
+        | '\u{20D0}'..='\u{20FF}'   // Combining marks for symbols, incl. keycaps
+        | '\u{FE00}'..='\u{FE0F}'   // Variation selectors
+        | '\u{FE20}'..='\u{FE2F}'   // Combining half marks
+        | '\u{1F3FB}'..='\u{1F3FF}' // Emoji skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}' // Tag characters (subdivision flags)
+        | '\u{E0100}'..='\u{E01EF}' // Variation selectors supplement
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combining_mark_stays_with_its_letter() {
+        assert_eq!(graphemes("cafe\u{0301}"), vec!["c", "a", "f", "e\u{0301}"]);
+        assert_eq!(count("e\u{0301}\u{0301}"), 1);
+    }
+
+    #[test]
+    fn regional_indicators_pair_into_flags() {
+        // 🇫🇷🇩🇪 is two flags, and a third lone indicator stands on its own.
+        let flags = "\u{1F1EB}\u{1F1F7}\u{1F1E9}\u{1F1EA}\u{1F1EC}";
+        assert_eq!(
+            graphemes(flags),
+            vec!["\u{1F1EB}\u{1F1F7}", "\u{1F1E9}\u{1F1EA}", "\u{1F1EC}"]
+        );
+    }
+
+    #[test]
+    fn zwj_emoji_sequence_is_one_cluster() {
+        // 👩‍👩‍👧 and 👍🏽 followed by a letter.
+        let family = "\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let thumbs = "\u{1F44D}\u{1F3FD}";
+        assert_eq!(graphemes(&format!("{}{}a", family, thumbs)), vec![family, thumbs, "a"]);
+    }
+
+    #[test]
+    fn hangul_jamo_form_one_syllable() {
+        // 한 as leading, vowel and trailing jamo, then the precomposed 글.
+        assert_eq!(graphemes("\u{1112}\u{1161}\u{11AB}\u{AE00}"), vec!["\u{1112}\u{1161}\u{11AB}", "\u{AE00}"]);
+        // A precomposed syllable without a final consonant takes a trailing jamo.
+        assert_eq!(graphemes("\u{AC00}\u{11A8}"), vec!["\u{AC00}\u{11A8}"]);
+        // One with a final consonant doesn't take a vowel.
+        assert_eq!(count("\u{AC01}\u{1161}"), 2);
+    }
+
+    #[test]
+    fn crlf_is_one_cluster_and_controls_take_no_marks() {
+        assert_eq!(graphemes("a\r\nb"), vec!["a", "\r\n", "b"]);
+        assert_eq!(graphemes("\n\u{0301}"), vec!["\n", "\u{0301}"]);
+    }
+
+    #[test]
+    fn editing_works_on_whole_clusters() {
+        let mut word = "ne\u{0301}e".to_string();
+        assert_eq!(prefix(&word, 2), "ne\u{0301}");
+        assert!(remove(&mut word, 1));
+        assert_eq!(word, "ne");
+        insert(&mut word, 1, 'a');
+        assert_eq!(word, "nae");
+        assert!(!remove(&mut word, 3));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::config::Keybindings;
+use crossterm::event::KeyCode;
+
+/// A control on the test and results screens that can be bound to another key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Restart,
+    Quit,
+    Advance,
+}
+
+/// The keys of `Keybindings`, checked and ready to match against key events.
+pub struct Bindings {
+    pub restart: KeyCode,
+    pub quit: KeyCode,
+    pub advance: KeyCode,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            restart: KeyCode::Tab,
+            quit: KeyCode::Esc,
+            advance: KeyCode::Char(' '),
+        }
+    }
+}
+
+impl Bindings {
+    /// Parses the configured key names, failing on an unknown name, on a single character
+    /// (it would be typed into the test or clash with the results-screen keys) or on two
+    /// actions sharing a key.
+    pub fn from_config(keybindings: &Keybindings) -> Result<Self, String> {
+        let parse = |action: &str, name: &str| {
+            if name.chars().count() == 1 {
+                return Err(format!(
+                    "'{}' for '{}' in keybindings is a typed character, not a named key",
+                    name, action
+                ));
+            }
+            parse_key(name).ok_or_else(|| format!("unknown key '{}' for '{}' in keybindings", name, action))
+        };
+        let bindings = Self {
+            restart: parse("restart", &keybindings.restart)?,
+            quit: parse("quit", &keybindings.quit)?,
+            advance: parse("advance", &keybindings.advance)?,
+        };
+        let keys = [bindings.restart, bindings.quit, bindings.advance];
+        if keys[0] == keys[1] || keys[0] == keys[2] || keys[1] == keys[2] {
+            return Err("two actions in keybindings share the same key".to_string());
+        }
+        Ok(bindings)
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        if key == self.restart {
+            Some(Action::Restart)
+        } else if key == self.quit {
+            Some(Action::Quit)
+        } else if key == self.advance {
+            Some(Action::Advance)
+        } else {
+            None
+        }
+    }
+}
+
+/// A key by the name used in the config file: "Tab", "Esc", "Space", "Enter" or "F1"
+/// to "F12" (any case).
+fn parse_key(name: &str) -> Option<KeyCode> {
+    match name.to_lowercase().as_str() {
+        "tab" => Some(KeyCode::Tab),
+        "esc" | "escape" => Some(KeyCode::Esc),
+        "space" => Some(KeyCode::Char(' ')),
+        "enter" => Some(KeyCode::Enter),
+        other => other
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=12).contains(n))
+            .map(KeyCode::F),
+    }
+}
+
+/// How a key is shown in on-screen hints, e.g. `Tab` or `F5`.
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keybindings(restart: &str, quit: &str, advance: &str) -> Keybindings {
+        Keybindings { restart: restart.to_string(), quit: quit.to_string(), advance: advance.to_string() }
+    }
+
+    #[test]
+    fn named_keys_parse_in_any_case() {
+        let bindings = Bindings::from_config(&keybindings("f5", "ESCAPE", "enter")).unwrap();
+        assert_eq!(bindings.restart, KeyCode::F(5));
+        assert_eq!(bindings.quit, KeyCode::Esc);
+        assert_eq!(bindings.advance, KeyCode::Enter);
+    }
+
+    #[test]
+    fn single_characters_are_rejected() {
+        for key in ["x", "w", "s", ";", " "] {
+            assert!(Bindings::from_config(&keybindings(key, "Esc", "Space")).is_err(), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn unknown_and_shared_keys_are_rejected() {
+        assert!(Bindings::from_config(&keybindings("F13", "Esc", "Space")).is_err());
+        assert!(Bindings::from_config(&keybindings("Tab", "Tab", "Space")).is_err());
+    }
+}
@@ -1,22 +1,68 @@
+mod bench;
+mod challenge;
+mod clipboard;
 mod config;
+mod error;
+mod frame;
+mod graphemes;
+mod keys;
 mod menu;
+mod practice;
+mod report;
+mod scoring;
 mod stats;
+mod ui;
+mod words;
+mod words_url;
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode},
-    style::{Color, Print, ResetColor, SetForegroundColor},
+    event::{self, DisableBracketedPaste, DisableFocusChange, EnableBracketedPaste, EnableFocusChange, Event, KeyCode},
+    style::{self, Color, Print, ResetColor},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
 use std::env;
+use std::fs;
 use std::io;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use chrono::Local;
+use error::TypingTestError;
+use frame::Style;
 
-fn main() -> io::Result<()> {
+const TIME_MODE_POOL_SIZE: usize = 100;
+const GOAL_BAR_WIDTH: u16 = 30;
+// The results screen compares against the average of the last BASELINE_TESTS results
+// for the mode, once there are at least MIN_BASELINE_TESTS of them.
+const BASELINE_TESTS: usize = 10;
+const MIN_BASELINE_TESTS: usize = 3;
+// Shortest quick drill; fewer missed words are repeated to fill it.
+const MIN_DRILL_WORDS: usize = 10;
+// Fewest words the difficulty and restrict_charset may leave before the full list is used.
+const MIN_RESTRICTED_WORDS: usize = 20;
+// With precise_timer, the remaining time shows tenths once it drops below this.
+const PRECISE_TIMER_SECS: f64 = 10.0;
+// Mistyped words listed on the results screen, and rows of words in the speed review.
+const MAX_REVIEW_WORDS: usize = 5;
+const MAX_SPEED_LINES: usize = 3;
+// Timed tests at least twice this long get a per-segment pace breakdown.
+const PACE_SEGMENT_SECS: usize = 15;
+// Most per-test lines on the session recap.
+const MAX_RECAP_LINES: usize = 10;
+// Most segments in an endless test's pace breakdown; longer tests get longer segments.
+const MAX_PACE_SEGMENTS: usize = 8;
+// How long without a keypress before the caret starts blinking, and the blink period.
+const CARET_IDLE_MS: u128 = 1000;
+const CARET_BLINK_MS: u128 = 500;
+// Results below this are false starts rather than tests.
+const MIN_COUNTED_WPM: f64 = 5.0;
+
+fn main() -> error::Result<()> {
     eprintln!("Starting main function.");
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
@@ -26,8 +72,66 @@ fn main() -> io::Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
-    let mut config = config::load_config();
+    // crossterm already honours NO_COLOR for its own styling; this covers dumb terminals too.
+    if !config::color_supported() {
+        style::force_color_output(false);
+    }
+
     let args: Vec<String> = env::args().collect();
+    if let Some(profile) = arg_value(&args, "--profile")
+        && let Err(e) = config::set_profile(profile)
+    {
+        eprintln!("{}", e);
+        return Ok(());
+    }
+
+    if args.contains(&"--prompt".to_string()) {
+        println!("{}", stats::prompt_summary(&config::load_config_light()));
+        return Ok(());
+    }
+
+    if let Some(i) = args.iter().position(|arg| arg == "--merge-modes") {
+        let (Some(from), Some(into)) = (args.get(i + 1), args.get(i + 2)) else {
+            eprintln!("usage: --merge-modes <from> <into>");
+            return Ok(());
+        };
+        return merge_modes(from, into);
+    }
+
+    // Undocumented: for checking scoring performance during development.
+    if let Some(value) = arg_value(&args, "--bench") {
+        match parse_positive::<usize>("--bench", value) {
+            Ok(tests) => bench::run(tests).iter().for_each(|line| println!("{}", line)),
+            Err(e) => eprintln!("{}", e),
+        }
+        return Ok(());
+    }
+
+    if args.contains(&"--progress".to_string()) {
+        let mode = arg_value(&args, "--progress").filter(|value| !value.starts_with('-'));
+        for line in stats::progress_lines(&config::load_config_light(), mode) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = arg_value(&args, "--report") {
+        let report = stats::markdown_report(&config::load_config_light());
+        match fs::write(path, report) {
+            Ok(()) => println!("Report written to {}", path),
+            Err(e) => eprintln!("Couldn't write the report to {}: {}", path, e),
+        }
+        return Ok(());
+    }
+
+    let persistence_error = config::config_path().err();
+    if let Some(reason) = &persistence_error {
+        eprintln!(
+            "Warning: running without persistence: {} (set {} to choose a config file).",
+            reason,
+            config::CONFIG_ENV_VAR
+        );
+    }
 
     let mut stdout = io::stdout();
     let mut rng = rand::thread_rng();
@@ -40,10 +144,26 @@ fn main() -> io::Result<()> {
         println!("OPTIONS:");
         println!("    -m, --menu              Opens the interactive settings menu.");
         println!("    -s, --stats             Shows your saved stats.");
+        println!("    --practice-weak         Builds tests from your most mistyped and slowest words.");
+        println!("    --no-save               Runs tests without saving results or any other data.");
+        println!("    --words-url <url>       Uses a word list downloaded from <url> for this session.");
+        println!("    --profile <name>        Uses separate settings and stats stored as config_<name>.json.");
+        println!("    --mode <words|time>     Runs a single test in this mode, then exits.");
+        println!("    --length <words>        Runs a single words test of this length, then exits.");
+        println!("    --time <seconds>        Runs a single timed test of this length, then exits.");
+        println!("    --playlist <name>       Runs the tests of a playlist from the config file in order.");
+        println!("    --flow                  Runs tests of random mode, length and difficulty within the config's flow bounds.");
+        println!("    --progress [mode]       Prints how fast your WPM is improving per mode, then exits.");
+        println!("    --challenge <path>      Runs the challenge in a JSON file once and shows pass or fail.");
+        println!("    --alphabet [shuffle]    Runs a warm-up of the 26 letters, optionally shuffled, without saving.");
+        println!("    --merge-modes <a> <b>   Moves the saved results of mode <a> into mode <b>, after asking.");
+        println!("    --prompt                Prints a one-line stats summary for shell prompts, then exits.");
+        println!("    --report <path>         Writes a Markdown summary of your stats per mode to <path>, then exits.");
         println!("    -h, --help              Prints this help message.");
         println!("EXAMPLES:");
         println!("    cargo run --             # Starts the typing test with current settings.");
         println!("    cargo run -- -m          # Opens the settings menu.");
+        println!("    cargo run -- --time 30   # Runs one 30-second test without changing your settings.");
         return Ok(());
     }
 
@@ -51,67 +171,416 @@ fn main() -> io::Result<()> {
         return menu::run();
     }
 
+    // A result picked in the stats view is typed again, once, with the same words.
+    let mut replay_next = None;
     if args.contains(&"-s".to_string()) || args.contains(&"--stats".to_string()) {
-        return stats::show_stats();
+        match stats::show_stats(!args.contains(&"--no-save".to_string()))? {
+            Some(replay) => replay_next = Some(replay),
+            None => return Ok(()),
+        }
+    }
+
+    // Only starting a test needs the word lists, so the language pack directory is
+    // scanned from here on.
+    let no_save = args.contains(&"--no-save".to_string());
+    let mut config = config::load_config(!no_save);
+    if let Some(problem) = replay_next.as_ref().and_then(|replay| replay_problem(&config, replay)) {
+        eprintln!("Can't replay this result: {}.", problem);
+        return Ok(());
+    }
+
+    let mut overrides = match RunOverrides::parse(&args) {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Ok(());
+        }
+    };
+    // A playlist runs its tests in order, each with its own settings, then ends the session.
+    let playlist = match arg_value(&args, "--playlist") {
+        Some(name) => match config.playlists.get(name) {
+            Some(entries) if !entries.is_empty() => Some(
+                entries
+                    .iter()
+                    .map(|entry| RunOverrides {
+                        game_mode: Some(entry.game_mode.clone()),
+                        test_length: entry.test_length,
+                        time_limit: entry.time_limit,
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            _ => {
+                eprintln!("no playlist named '{}' in the config file", name);
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let challenge = match arg_value(&args, "--challenge") {
+        Some(_) if playlist.is_some() => {
+            eprintln!("--challenge can't be combined with --playlist");
+            return Ok(());
+        }
+        Some(path) => match challenge::Challenge::load(path) {
+            Ok(challenge) => Some(challenge),
+            Err(e) => {
+                eprintln!("Could not load challenge {}: {}", path, e);
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    // A one-off warm-up of the 26 letters, in order or shuffled with `--alphabet shuffle`.
+    let alphabet = args.contains(&"--alphabet".to_string());
+    if alphabet && (challenge.is_some() || playlist.is_some()) {
+        eprintln!("--alphabet can't be combined with --challenge or --playlist");
+        return Ok(());
+    }
+    // A session of tests whose mode, length and difficulty are picked at random each time.
+    let flow = args.contains(&"--flow".to_string());
+    if flow && (alphabet || challenge.is_some() || playlist.is_some() || overrides.is_some()) {
+        eprintln!("--flow can't be combined with --alphabet, --challenge, --playlist or a mode, length or time");
+        return Ok(());
+    }
+    let alphabet_words = alphabet.then(|| {
+        let mut letters: Vec<String> = ('a'..='z').map(String::from).collect();
+        if arg_value(&args, "--alphabet") == Some("shuffle") {
+            letters.shuffle(&mut rng);
+        }
+        letters
+    });
+    // Challenge and warm-up text is typed as given rather than drawn from a word list.
+    let fixed_text = challenge.as_ref().map(|challenge| challenge.words()).or(alphabet_words);
+    // A test started straight from the command line exits after its results.
+    let single_run = (overrides.is_some() || fixed_text.is_some() || replay_next.is_some()) && playlist.is_none();
+    // Otherwise the first schedule window covering the current time picks the test settings.
+    if !single_run
+        && playlist.is_none()
+        && !flow
+        && let Some(entry) = config.schedule.iter().find(|entry| entry.contains(Local::now().time()))
+    {
+        overrides = RunOverrides {
+            game_mode: entry.game_mode.clone(),
+            test_length: entry.test_length,
+            time_limit: entry.time_limit,
+        };
     }
 
-    stdout.execute(EnterAlternateScreen)?;
-    terminal::enable_raw_mode()?;
+    let practice_weak = args.contains(&"--practice-weak".to_string());
+    // Undocumented: overlays render timings for diagnosing input lag.
+    let debug = args.contains(&"--debug".to_string());
+
+    // New users pick their settings before the first test; whatever they saved is
+    // loaded back for the session.
+    if !config.tutorial_shown
+        && config.prompt_menu_on_first_run
+        && !single_run
+        && playlist.is_none()
+        && !no_save
+        && persistence_error.is_none()
+    {
+        menu::run_first_time()?;
+        config = config::load_config(true);
+    }
+    let bindings = keys::Bindings::from_config(&config.keybindings).unwrap_or_else(|e| {
+        eprintln!("Warning: {}; using the default keys.", e);
+        keys::Bindings::default()
+    });
+    let restart_key = keys::key_name(bindings.restart);
+    let quit_key = keys::key_name(bindings.quit);
+
+    let url_words = match arg_value(&args, "--words-url") {
+        Some(url) => match words_url::load_words(url) {
+            Ok(words) => Some(words),
+            Err(e) => {
+                eprintln!("Could not load words from {}: {}", url, e);
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let session_words = if url_words.is_some() {
+        url_words
+    } else if config.selected_language == config::MIXED_LANGUAGE {
+        Some(words::mixed_word_list(&config.language_packs))
+    } else {
+        None
+    };
+
+    // Results from a downloaded list are kept apart from the installed language packs.
+    let language_key = if arg_value(&args, "--words-url").is_some() {
+        "url".to_string()
+    } else {
+        config.selected_language.clone()
+    };
+
+    stdout
+        .execute(EnterAlternateScreen)
+        .and_then(|stdout| stdout.execute(EnableBracketedPaste))
+        .map_err(TypingTestError::Terminal)?;
+    if config.pause_on_blur {
+        stdout.execute(EnableFocusChange).map_err(TypingTestError::Terminal)?;
+    }
+    terminal::enable_raw_mode().map_err(TypingTestError::Terminal)?;
+    ui::apply_ui_colors(&mut stdout, &config)?;
+    stdout.execute(terminal::Clear(terminal::ClearType::All))?;
+
+    if !config.tutorial_shown {
+        show_tutorial(&mut stdout, &config, &bindings)?;
+        config.tutorial_shown = true;
+        if !no_save && persistence_error.is_none() {
+            config::save_config(&config)?;
+        }
+    }
+
+    let fallback_words: Vec<String> = config::FALLBACK_WORDS.iter().map(|word| word.to_string()).collect();
+
+    // WPM of every finished test this session, for the recap on exit.
+    let mut session_wpms: Vec<f64> = Vec::new();
+    // Words missed in the last test, when 'w' on its results screen asked for a drill.
+    let mut drill_words: Option<Vec<String>> = None;
+    // Position in the playlist, and one summary line per playlist or flow test for the recap.
+    let mut playlist_index = 0;
+    let mut recap_lines: Vec<String> = Vec::new();
+    // A summary card from the stats screen that couldn't be copied, printed on exit.
+    let mut card_to_print: Option<String> = None;
 
     while running.load(Ordering::SeqCst) {
-        match (|| -> io::Result<()> {
-            let game_mode = config.game_mode.clone();
-            let num_words = config.default_test_length;
-            let time_limit = config.default_time_limit;
-            let layout_theme = config.layout_theme.clone();
+        match (|| -> error::Result<()> {
+            let drill_list = drill_words.take();
+            let drill = drill_list.is_some();
+            let fixed_words = fixed_text.as_ref().filter(|_| !drill);
+            // Fixed texts have nothing to replay into.
+            let replay = replay_next.take().filter(|_| fixed_text.is_none() && !drill);
+            let replay_overrides = replay.as_ref().map(|replay| RunOverrides {
+                game_mode: Some(replay.mode.game_mode.clone()),
+                test_length: matches!(replay.mode.game_mode, config::GameMode::Words).then_some(replay.mode.amount as usize),
+                time_limit: matches!(replay.mode.game_mode, config::GameMode::Time).then_some(replay.mode.amount),
+            });
+            let flow_test = (flow && replay.is_none() && !drill).then(|| pick_flow_test(&config.flow, &mut rng));
+            let flow_notice = flow_test.as_ref().map(|(_, difficulty)| format!("Flow session, {:?} words", difficulty));
+            let run_overrides = replay_overrides
+                .as_ref()
+                .or(flow_test.as_ref().map(|(overrides, _)| overrides))
+                .unwrap_or_else(|| playlist.as_ref().map_or(&overrides, |list| &list[playlist_index]));
+            // The last test of a playlist, or a single run, has no next test to move on to.
+            let ends_session = single_run || playlist.as_ref().is_some_and(|list| playlist_index + 1 == list.len());
+            let game_mode = if drill {
+                config::GameMode::Words
+            } else if let Some(challenge) = &challenge {
+                match challenge.time_limit {
+                    Some(_) => config::GameMode::Time,
+                    None => config::GameMode::Words,
+                }
+            } else if alphabet {
+                config::GameMode::Words
+            } else {
+                run_overrides.game_mode.clone().unwrap_or(config.game_mode.clone())
+            };
+            let num_words = match (&drill_list, fixed_words) {
+                (Some(list), _) => list.len().max(MIN_DRILL_WORDS),
+                (None, Some(words)) => words.len(),
+                (None, None) => run_overrides.test_length.unwrap_or(config.default_test_length),
+            };
+            let time_limit = challenge
+                .as_ref()
+                .and_then(|challenge| challenge.time_limit)
+                .or(run_overrides.time_limit)
+                .unwrap_or(config.default_time_limit);
+            let endless = matches!(game_mode, config::GameMode::Time) && time_limit == config::NO_TIME_LIMIT;
+            let layout_theme = if config.random_layout {
+                if rng.gen_bool(0.5) {
+                    config::LayoutTheme::Default
+                } else {
+                    config::LayoutTheme::Boxes
+                }
+            } else {
+                config.layout_theme.clone()
+            };
+            let case_insensitive = config.case_insensitive;
+            let live_wpm_smoothing = config.live_wpm_smoothing.clamp(0.0, 0.95);
+
+            let weak_word_list = if practice_weak && replay.is_none() {
+                practice::weak_word_list(&config.weak_words)
+            } else {
+                None
+            };
+            let notice = if drill {
+                Some("Quick drill of the words you just missed")
+            } else if replay.is_some() {
+                Some("Replaying a saved test")
+            } else if let Some(challenge) = &challenge {
+                challenge.name.as_deref()
+            } else if alphabet {
+                Some("Alphabet warm-up")
+            } else if let Some(flow_notice) = &flow_notice {
+                Some(flow_notice.as_str())
+            } else if practice_weak && weak_word_list.is_none() {
+                Some("Not enough weak-word data yet, using the normal word list")
+            } else {
+                None
+            };
 
-            let current_word_list: &Vec<String> = &config
+            // A replay uses the language it was saved under rather than the session's.
+            let language = replay.as_ref().map_or(&config.selected_language, |replay| &replay.mode.language);
+            let language_key = replay.as_ref().map_or(language_key.clone(), |replay| replay.mode.language.clone());
+            let replay_words = replay
+                .as_ref()
+                .filter(|replay| replay.mode.language == config::MIXED_LANGUAGE)
+                .map(|_| words::mixed_word_list(&config.language_packs));
+            let list_words = if replay.is_some() { replay_words.as_ref() } else { session_words.as_ref() };
+            let selected_pack = config
                 .language_packs
                 .iter()
-                .find(|p| p.name == config.selected_language)
-                .unwrap()
-                .words;
-
-            let (mut words_to_type, mut user_typed_words) = match game_mode {
-                config::GameMode::Words => {
-                    let w: Vec<String> = current_word_list
-                        .choose_multiple(&mut rng, num_words)
-                        .cloned()
-                        .collect();
-                    let u = vec![String::new(); w.len()];
-                    (w, u)
+                .find(|p| p.name == *language);
+            let word_source: Option<(&Vec<String>, Option<&[f64]>)> =
+                match drill_list.as_ref().or(weak_word_list.as_ref()).or(list_words) {
+                    Some(list) => Some((list, None)),
+                    None => selected_pack.map(|pack| (&pack.words, pack.frequencies.as_deref())),
+                };
+            // With no packs installed, or an empty one, use the built-in list rather than
+            // showing an empty test.
+            let word_source = word_source.filter(|(list, _)| !list.is_empty());
+            let notice = match word_source {
+                Some(_) => notice,
+                None if fixed_words.is_some() => notice,
+                None => Some("No word list found, using a small built-in list"),
+            };
+            let (current_word_list, pack_frequencies) = word_source.unwrap_or((&fallback_words, None));
+            let word_settings = config::WordSettings {
+                restrict_charset: config.restrict_charset,
+                frequency_weighted: config.frequency_weighted,
+                split_on_hyphen: config.split_on_hyphen,
+                list_fingerprint: words::fingerprint(current_word_list, pack_frequencies),
+            };
+            let split_words = config
+                .split_on_hyphen
+                .then(|| words::split_hyphenated(current_word_list, pack_frequencies))
+                .filter(|(list, _)| !list.is_empty());
+            let (current_word_list, pack_frequencies) = match &split_words {
+                Some((list, frequencies)) => (list, frequencies.as_deref()),
+                None => (current_word_list, pack_frequencies),
+            };
+            // Only the words the difficulty and character set allow, unless too little of the
+            // list is left to be useful.
+            let charset = config.restrict_charset;
+            let difficulty = match (&replay, &flow_test) {
+                (Some(replay), _) => replay.mode.difficulty,
+                (None, Some((_, difficulty))) => *difficulty,
+                (None, None) => config.word_list_difficulty,
+            };
+            let filtering = charset != config::Charset::Any || difficulty != config::WordListDifficulty::Medium;
+            let restricted = (filtering && !drill && fixed_words.is_none()).then(|| {
+                words::filter(current_word_list, pack_frequencies, |word| {
+                    charset.allows(word) && difficulty.allows(word)
+                })
+            });
+            let notice = match &restricted {
+                Some((list, _)) if list.len() < MIN_RESTRICTED_WORDS => {
+                    Some("Too few words match the difficulty or restrict_charset, using the full list")
                 }
-                config::GameMode::Time => {
-                    let mut word_pool: Vec<String> = Vec::new();
-                    for _ in 0..10 {
-                        word_pool.extend(
-                            current_word_list
-                                .choose_multiple(&mut rng, current_word_list.len())
-                                .cloned(),
-                        );
-                    }
-                    let u = vec![String::new(); word_pool.len()];
-                    (word_pool, u)
+                _ => notice,
+            };
+            let (current_word_list, pack_frequencies, difficulty) = match &restricted {
+                Some((list, frequencies)) if list.len() >= MIN_RESTRICTED_WORDS => {
+                    (list, frequencies.as_deref(), difficulty)
                 }
+                // Without the filter the test is the full list, so it counts as Medium.
+                _ => (current_word_list, pack_frequencies, config::WordListDifficulty::Medium),
+            };
+            // Weak-word drills stay uniform so every problem word gets practiced.
+            let weights = if config.frequency_weighted && weak_word_list.is_none() && !drill {
+                Some(words::frequency_weights(current_word_list, pack_frequencies))
+            } else {
+                None
+            };
+            let mut sampler = words::WordSampler::new(current_word_list, weights);
+
+            // Time mode starts with a modest pool and tops it up as the user types.
+            let pool_size = match game_mode {
+                config::GameMode::Words => num_words,
+                config::GameMode::Time => TIME_MODE_POOL_SIZE,
+            };
+            // Words come from their own seeded generator, so a saved result can be replayed
+            // with the same words.
+            let mut seed: u64 = replay.as_ref().map_or_else(|| rng.r#gen(), |replay| replay.seed);
+            let mut word_rng = StdRng::seed_from_u64(seed);
+            let mut words_to_type = match fixed_words {
+                Some(words) => words.clone(),
+                None => sampler.build(pool_size, &mut word_rng),
+            };
+            let mut user_typed_words = vec![String::new(); words_to_type.len()];
+
+            let mode_info = config::ModeInfo {
+                game_mode: game_mode.clone(),
+                amount: match game_mode {
+                    config::GameMode::Words => num_words as u64,
+                    config::GameMode::Time => time_limit,
+                },
+                language: language_key.clone(),
+                difficulty,
             };
+            let key = mode_info.key();
+            // Average of the last few saved results for this mode, shown as a target before
+            // the test starts and compared against on the results screen. Fixed texts aren't
+            // comparable with the random words of the same mode.
+            let recent_average = config.results.get(&key).filter(|_| fixed_words.is_none()).and_then(|history| {
+                let recent = &history[history.len().saturating_sub(BASELINE_TESTS)..];
+                (recent.len() >= MIN_BASELINE_TESTS)
+                    .then(|| recent.iter().map(|r| r.wpm).sum::<f64>() / recent.len() as f64)
+            });
 
+            let mut word_times: Vec<Option<f64>> = vec![None; words_to_type.len()];
             let mut current_word_index = 0;
             let mut start_time: Option<Instant> = None;
+            let mut word_start: Option<Instant> = None;
+            let mut last_keypress = Instant::now();
+            let mut word_corrections: u32 = 0;
+            // Consecutive words finished without a mistake, and the longest such run.
+            let mut combo: u32 = 0;
+            let mut max_combo: u32 = 0;
+            let mut auto_advanced = false;
+            let mut pasted: VecDeque<KeyCode> = VecDeque::new();
+            // With `pause_on_blur`, when the clocks were last pushed forward while the
+            // window is unfocused.
+            let mut focused = true;
+            let mut paused_at: Option<Instant> = None;
+            let mut debug_stats = DebugStats::new();
+            // Correct characters typed by the end of each second, for the pace breakdown.
+            let mut timeline: Vec<usize> = Vec::new();
+            // What is on screen now, so each frame only redraws the cells that changed.
+            let mut previous_frame: Option<frame::Frame> = None;
+            let mut failed = false;
+            // Characters between the caret and the end of the current word; typing and
+            // deleting happen at the caret.
+            let mut caret_back = 0;
+            // Left with Esc rather than finished.
+            let mut aborted = false;
             let mut last_wpm_update: Option<Instant> = None;
             let mut wpm = 0.0;
+            let mut kpm = 0.0;
 
             loop {
                 if !running.load(Ordering::SeqCst) {
                     break;
                 }
 
+                // Time away from the window doesn't count: move the clocks forward by it.
+                if let Some(paused) = paused_at {
+                    let away = paused.elapsed();
+                    start_time = start_time.map(|start| start + away);
+                    word_start = word_start.map(|start| start + away);
+                    paused_at = (!focused).then(Instant::now);
+                }
+
                 let mut game_over = false;
                 match game_mode {
                     config::GameMode::Time => {
-                        if let Some(start) = start_time {
-                            if start.elapsed().as_secs() >= time_limit {
-                                game_over = true;
-                            }
+                        if let Some(start) = start_time
+                            && !endless
+                            && start.elapsed().as_secs() >= time_limit
+                        {
+                            game_over = true;
                         }
                     }
                     config::GameMode::Words => {
@@ -124,23 +593,22 @@ fn main() -> io::Result<()> {
                     break;
                 }
 
+                let frame_start = Instant::now();
                 let (width, height) = terminal::size()?;
 
+                if let Some(start) = start_time {
+                    let elapsed = start.elapsed().as_secs() as usize;
+                    while timeline.len() < elapsed {
+                        timeline.push(scoring::correct_chars(&user_typed_words, &words_to_type, case_insensitive, &config.optional_chars));
+                    }
+                }
+
                 if last_wpm_update.is_none() || last_wpm_update.unwrap().elapsed().as_secs() >= 1 {
-                    let correct_chars_total: usize = user_typed_words
-                        .iter()
-                        .zip(words_to_type.iter())
-                        .map(|(typed, original)| {
-                            typed
-                                .chars()
-                                .zip(original.chars())
-                                .filter(|(a, b)| a == b)
-                                .count()
-                        })
-                        .sum();
+                    let correct_chars_total =
+                        scoring::correct_chars(&user_typed_words, &words_to_type, case_insensitive, &config.optional_chars);
 
                     let elapsed_seconds = if let Some(start) = start_time {
-                        start.elapsed().as_secs_f64()
+                        scoring::with_first_key_grace(start.elapsed().as_secs_f64(), config.first_key_grace_ms)
                     } else {
                         0.0
                     };
@@ -150,97 +618,120 @@ fn main() -> io::Result<()> {
                     } else {
                         0.0
                     };
-                    wpm = cpm / 5.0;
+                    kpm = if elapsed_seconds > 0.0 {
+                        let typed = scoring::keystrokes(&user_typed_words, current_word_index + 1);
+                        (typed as f64 / elapsed_seconds) * 60.0
+                    } else {
+                        0.0
+                    };
+                    let raw_wpm = cpm / 5.0;
+                    // Only the displayed value is smoothed; the final score is computed separately.
+                    wpm = if wpm > 0.0 {
+                        live_wpm_smoothing * wpm + (1.0 - live_wpm_smoothing) * raw_wpm
+                    } else {
+                        raw_wpm
+                    };
                     last_wpm_update = Some(Instant::now());
                 }
 
-                stdout
-                    .execute(cursor::MoveTo(0, 2))?
-                    .execute(terminal::Clear(terminal::ClearType::FromCursorDown))?;
+                // Whole numbers flicker less mid-test; the results screen keeps two decimals.
+                let wpm_precision = if config.live_wpm_integer { 0 } else { 2 };
+                let mut top_bar_text = match game_mode {
+                    config::GameMode::Time => {
+                        let elapsed = start_time.map_or(0.0, |s| s.elapsed().as_secs_f64());
+                        let remaining = (time_limit as f64 - elapsed).max(0.0);
+                        // The input poll redraws every 50ms, often enough for tenths.
+                        if endless {
+                            format!("WPM: {} | Elapsed: {}s", ui::format_decimal(&config, wpm, wpm_precision), elapsed as u64)
+                        } else if config.precise_timer && remaining < PRECISE_TIMER_SECS {
+                            format!(
+                                "WPM: {} | Time: {}",
+                                ui::format_decimal(&config, wpm, wpm_precision),
+                                ui::format_decimal(&config, remaining, 1)
+                            )
+                        } else {
+                            let remaining = time_limit.saturating_sub(elapsed as u64);
+                            format!("WPM: {} | Time: {}", ui::format_decimal(&config, wpm, wpm_precision), remaining)
+                        }
+                    }
+                    config::GameMode::Words => format!("WPM: {}", ui::format_decimal(&config, wpm, wpm_precision)),
+                };
+                if config.show_kpm {
+                    top_bar_text = format!("{} | KPM: {:.0}", top_bar_text, kpm);
+                }
+                top_bar_text = format!("{} | Combo: {}", top_bar_text, combo);
+                // Timed tests with a goal show the finished words against the target pace.
+                if let (config::GameMode::Time, Some(target_wpm), Some(start)) =
+                    (&game_mode, config.target_wpm, start_time)
+                {
+                    let expected = target_wpm * start.elapsed().as_secs_f64() / 60.0;
+                    let lead = (current_word_index as f64 - expected).round() as i64;
+                    let unit = if lead.abs() == 1 { "word" } else { "words" };
+                    top_bar_text = format!("{} | {:+} {}", top_bar_text, lead, unit);
+                }
+
+                // Where someone typing steadily at the target pace would be by now.
+                let ghost = match (config.ghost_pace, config.target_wpm, start_time) {
+                    (true, Some(target_wpm), Some(start)) => {
+                        let chars = target_wpm * 5.0 * start.elapsed().as_secs_f64() / 60.0;
+                        scoring::position_at(&words_to_type, chars as usize)
+                    }
+                    _ => None,
+                };
+                if let Some(max_corrections) = config.max_corrections_per_word {
+                    let left = max_corrections.saturating_sub(word_corrections);
+                    top_bar_text = format!("{} | Corrections left: {}", top_bar_text, left);
+                }
+                if let Some(average) = recent_average.filter(|_| config.show_recent_average && start_time.is_none()) {
+                    top_bar_text = format!("{} | Recent avg: {}", top_bar_text, ui::format_decimal(&config, average, 1));
+                }
+                if let Some(notice) = notice {
+                    top_bar_text = format!("{} | {}", top_bar_text, notice);
+                }
+                if paused_at.is_some() {
+                    top_bar_text = format!("{} | Paused", top_bar_text);
+                }
+
+                let mut frame = frame::Frame::new(width, height);
                 match layout_theme {
                     config::LayoutTheme::Default => {
                         let text_block = words_to_type.join(" ");
-                        let text_width = text_block.len() as u16;
+                        // `max_line_width` keeps lines short enough to follow on wide terminals.
+                        let line_width = config.max_line_width.map_or(width, |max| width.min(max.max(10)));
+                        let text_width = (graphemes::count(&text_block) as u16).min(line_width);
                         let start_x = (width.saturating_sub(text_width)) / 2;
                         let start_y = height / 2;
-
-                        let top_bar_text = match game_mode {
-                            config::GameMode::Time => {
-                                let elapsed = start_time.map_or(0, |s| s.elapsed().as_secs());
-                                let remaining = time_limit.saturating_sub(elapsed);
-                                format!("WPM: {:.2} | Time: {}", wpm, remaining)
-                            }
-                            config::GameMode::Words => format!("WPM: {:.2}", wpm),
+                        let bar_y = match config.stats_bar_position {
+                            config::StatsBarPosition::Top => start_y - 2,
+                            config::StatsBarPosition::Bottom => height.saturating_sub(2),
                         };
 
-                        stdout
-                            .execute(cursor::MoveTo(start_x, start_y - 2))?
-                            .execute(Print(top_bar_text))?;
+                        frame.print(start_x, bar_y, &top_bar_text, Style::default());
+                        if start_time.is_none() {
+                            draw_ready_hint(&mut frame, &config, width, start_y - 1);
+                        }
 
                         let mut x = start_x;
                         let mut y = start_y;
 
                         for (i, word) in words_to_type.iter().enumerate() {
-                            let word_len = word.len() as u16;
-                            if x + word_len > width {
+                            let word_len = graphemes::count(word) as u16;
+                            if x + word_len > start_x + line_width {
                                 y += 2;
                                 x = start_x;
                             }
 
-                            if i == current_word_index {
-                                let typed_text = &user_typed_words[i];
-                                for (char_i, char) in word.chars().enumerate() {
-                                    if char_i < typed_text.len() {
-                                        if typed_text.chars().nth(char_i).unwrap() == char {
-                                            stdout.execute(SetForegroundColor(Color::from(
-                                                config.color_theme.correct,
-                                            )))?;
-                                        } else {
-                                            stdout.execute(SetForegroundColor(Color::from(
-                                                config.color_theme.incorrect,
-                                            )))?;
-                                        }
-                                    } else {
-                                        stdout.execute(SetForegroundColor(Color::from(
-                                            config.color_theme.default,
-                                        )))?;
-                                    }
-                                    stdout
-                                        .execute(cursor::MoveTo(x + char_i as u16, y))?
-                                        .execute(Print(char))?;
-                                }
-                                if typed_text.len() > word.len() {
-                                    stdout.execute(SetForegroundColor(Color::from(
-                                        config.color_theme.incorrect,
-                                    )))?;
-                                    for (char_i, char) in
-                                        typed_text.chars().skip(word.len()).enumerate()
-                                    {
-                                        stdout
-                                            .execute(cursor::MoveTo(
-                                                x + word.len() as u16 + char_i as u16,
-                                                y,
-                                            ))?
-                                            .execute(Print(char))?;
-                                    }
-                                }
-                            } else {
-                                let typed_word = &user_typed_words[i];
-                                for (char_i, original_char) in word.chars().enumerate() {
-                                    let color = if char_i < typed_word.len() {
-                                        if typed_word.chars().nth(char_i).unwrap() == original_char {
-                                            Color::from(config.color_theme.correct)
-                                        } else {
-                                            Color::from(config.color_theme.incorrect)
-                                        }
-                                    } else {
-                                        Color::DarkGrey
-                                    };
-                                    stdout
-                                        .execute(SetForegroundColor(color))?
-                                        .execute(cursor::MoveTo(x + char_i as u16, y))?
-                                        .execute(Print(original_char))?;
-                                }
+                            // Skipped words leave their space blank, so the layout doesn't move.
+                            if !(config.hide_upcoming_words && i > current_word_index) {
+                                ui::draw_word(
+                                    &mut frame,
+                                    &config,
+                                    word,
+                                    &user_typed_words[i],
+                                    i == current_word_index,
+                                    (x, y),
+                                    ghost.filter(|(word_i, _)| *word_i == i).map(|(_, char_i)| char_i),
+                                );
                             }
                             x += word_len + 1;
                         }
@@ -249,41 +740,14 @@ fn main() -> io::Result<()> {
                         let box_width = (width as f32 * 0.8).max(40.0) as u16;
                         let box_start_x = (width - box_width) / 2;
 
-                        // --- WPM/Timer Box ---
-                        let top_bar_text = match game_mode {
-                            config::GameMode::Time => {
-                                let elapsed = start_time.map_or(0, |s| s.elapsed().as_secs());
-                                let remaining = time_limit.saturating_sub(elapsed);
-                                format!("WPM: {:.2} | Time: {}", wpm, remaining)
-                            }
-                            config::GameMode::Words => format!("WPM: {:.2}", wpm),
-                        };
-                        let wpm_box_start_y: u16 = 2;
-                        let wpm_box_content_x = box_start_x + 2;
-                        let wpm_box_content_y = wpm_box_start_y + 1;
-
-                        stdout
-                            .execute(cursor::MoveTo(box_start_x, wpm_box_start_y))?
-                            .execute(Print("┌".to_string() + &"─".repeat((box_width - 2) as usize) + "┐"))?;
-                        stdout
-                            .execute(cursor::MoveTo(box_start_x, wpm_box_start_y + 1))?
-                            .execute(Print("│".to_string() + &" ".repeat((box_width - 2) as usize) + "│"))?;
-                        stdout
-                            .execute(cursor::MoveTo(box_start_x, wpm_box_start_y + 2))?
-                            .execute(Print("└".to_string() + &"─".repeat((box_width - 2) as usize) + "┘"))?;
-                        stdout
-                            .execute(cursor::MoveTo(wpm_box_content_x, wpm_box_content_y))?
-                            .execute(Print(top_bar_text))?;
-
-                        // --- Main Text Box ---
-                        let main_box_start_y: u16 = wpm_box_start_y + 4;
+                        let main_box_start_y = boxes_main_start_y(&config);
                         let text_area_start_x = box_start_x + 2;
                         let text_area_width = box_width - 4;
-                        
+
                         let mut temp_x = 0;
                         let mut num_lines = 1;
                         for word in words_to_type.iter() {
-                            let word_len = word.len() as u16;
+                            let word_len = graphemes::count(word) as u16;
                             if temp_x + word_len > text_area_width {
                                 num_lines += 1;
                                 temp_x = 0;
@@ -293,78 +757,71 @@ fn main() -> io::Result<()> {
 
                         let main_box_height = num_lines + 1;
 
-                        stdout
-                            .execute(cursor::MoveTo(box_start_x, main_box_start_y))?
-                            .execute(Print("┌".to_string() + &"─".repeat((box_width - 2) as usize) + "┐"))?;
+                        // --- WPM/Timer Box ---
+                        // Below the text box it follows the text box's height.
+                        let wpm_box_start_y: u16 = match config.stats_bar_position {
+                            config::StatsBarPosition::Top => 2,
+                            config::StatsBarPosition::Bottom => main_box_start_y + main_box_height + 3,
+                        };
+                        let wpm_box_content_x = box_start_x + 2;
+                        let wpm_box_content_y = wpm_box_start_y + 1;
+
+                        let top = "┌".to_string() + &"─".repeat((box_width - 2) as usize) + "┐";
+                        let side = "│".to_string() + &" ".repeat((box_width - 2) as usize) + "│";
+                        let bottom = "└".to_string() + &"─".repeat((box_width - 2) as usize) + "┘";
+                        frame.print(box_start_x, wpm_box_start_y, &top, Style::default());
+                        frame.print(box_start_x, wpm_box_start_y + 1, &side, Style::default());
+                        frame.print(box_start_x, wpm_box_start_y + 2, &bottom, Style::default());
+                        frame.print(wpm_box_content_x, wpm_box_content_y, &top_bar_text, Style::default());
+
+                        // --- Main Text Box ---
+                        if start_time.is_none() {
+                            draw_ready_hint(&mut frame, &config, width, main_box_start_y - 1);
+                        }
+
+                        frame.print(box_start_x, main_box_start_y, &top, Style::default());
                         for i in 0..main_box_height {
-                            stdout
-                                .execute(cursor::MoveTo(box_start_x, main_box_start_y + 1 + i))?
-                                .execute(Print("│".to_string() + &" ".repeat((box_width - 2) as usize) + "│"))?;
+                            frame.print(box_start_x, main_box_start_y + 1 + i, &side, Style::default());
                         }
-                        stdout
-                            .execute(cursor::MoveTo(box_start_x, main_box_start_y + main_box_height + 1))?
-                            .execute(Print("└".to_string() + &"─".repeat((box_width - 2) as usize) + "┘"))?;
+                        frame.print(box_start_x, main_box_start_y + main_box_height + 1, &bottom, Style::default());
 
                         // --- Render Text Inside Box ---
                         let mut x = text_area_start_x;
                         let mut y = main_box_start_y + 1;
 
                         for (i, word) in words_to_type.iter().enumerate() {
-                            let word_len = word.len() as u16;
+                            let word_len = graphemes::count(word) as u16;
                             if x + word_len > text_area_start_x + text_area_width {
                                 y += 1;
                                 x = text_area_start_x;
                             }
 
-                            if i == current_word_index {
-                                let typed_text = &user_typed_words[i];
-                                for (char_i, char) in word.chars().enumerate() {
-                                    if char_i < typed_text.len() {
-                                        if typed_text.chars().nth(char_i).unwrap() == char {
-                                            stdout.execute(SetForegroundColor(Color::from(config.color_theme.correct)))?;
-                                        } else {
-                                            stdout.execute(SetForegroundColor(Color::from(config.color_theme.incorrect)))?;
-                                        }
-                                    } else {
-                                        stdout.execute(SetForegroundColor(Color::from(config.color_theme.default)))?;
-                                    }
-                                    stdout.execute(cursor::MoveTo(x + char_i as u16, y))?.execute(Print(char))?;
-                                }
-                                if typed_text.len() > word.len() {
-                                    stdout.execute(SetForegroundColor(Color::from(config.color_theme.incorrect)))?;
-                                    for (char_i, char) in typed_text.chars().skip(word.len()).enumerate() {
-                                        stdout.execute(cursor::MoveTo(x + word.len() as u16 + char_i as u16, y))?.execute(Print(char))?;
-                                    }
-                                }
-                            } else {
-                                let typed_word = &user_typed_words[i];
-                                for (char_i, original_char) in word.chars().enumerate() {
-                                    let color = if char_i < typed_word.len() {
-                                        if typed_word.chars().nth(char_i).unwrap() == original_char {
-                                            Color::from(config.color_theme.correct)
-                                        } else {
-                                            Color::from(config.color_theme.incorrect)
-                                        }
-                                    } else {
-                                        Color::DarkGrey
-                                    };
-                                    stdout.execute(SetForegroundColor(color))?.execute(cursor::MoveTo(x + char_i as u16, y))?.execute(Print(original_char))?;
-                                }
+                            if !(config.hide_upcoming_words && i > current_word_index) {
+                                ui::draw_word(
+                                    &mut frame,
+                                    &config,
+                                    word,
+                                    &user_typed_words[i],
+                                    i == current_word_index,
+                                    (x, y),
+                                    ghost.filter(|(word_i, _)| *word_i == i).map(|(_, char_i)| char_i),
+                                );
                             }
                             x += word_len + 1;
                         }
                     }
                 }
 
-                stdout.execute(ResetColor)?;
-
                 let cursor_x;
                 let cursor_y;
+                let typed = &user_typed_words[current_word_index];
+                let typed_before_caret = graphemes::prefix(typed, graphemes::count(typed) - caret_back);
 
                 match layout_theme {
                     config::LayoutTheme::Default => {
                         let text_block = words_to_type.join(" ");
-                        let text_width = text_block.len() as u16;
+                        let line_width = config.max_line_width.map_or(width, |max| width.min(max.max(10)));
+                        let text_width = (graphemes::count(&text_block) as u16).min(line_width);
                         let start_x = (width.saturating_sub(text_width)) / 2;
                         let start_y = height / 2;
 
@@ -373,21 +830,20 @@ fn main() -> io::Result<()> {
 
                         // Recalculate position considering wrapping
                         for word in words_to_type.iter().take(current_word_index) {
-                            let word_len = word.len() as u16;
-                            if x + word_len > width {
+                            let word_len = graphemes::count(word) as u16;
+                            if x + word_len > start_x + line_width {
                                 y += 2; // The original code did this
                                 x = start_x;
                             }
                             x += word_len + 1;
                         }
-                        cursor_x = x + user_typed_words[current_word_index].len() as u16;
+                        cursor_x = x + ui::caret_offset(&config, &words_to_type[current_word_index], typed_before_caret);
                         cursor_y = y;
                     }
                     config::LayoutTheme::Boxes => {
                         let box_width = (width as f32 * 0.8).max(40.0) as u16;
                         let box_start_x = (width - box_width) / 2;
-                        let wpm_box_start_y: u16 = 2;
-                        let main_box_start_y: u16 = wpm_box_start_y + 4;
+                        let main_box_start_y = boxes_main_start_y(&config);
                         let text_area_start_x = box_start_x + 2;
                         let text_area_width = box_width - 4;
 
@@ -395,73 +851,247 @@ fn main() -> io::Result<()> {
                         let mut y = main_box_start_y + 1;
 
                         for word in words_to_type.iter().take(current_word_index) {
-                            let word_len = word.len() as u16;
+                            let word_len = graphemes::count(word) as u16;
                             if x + word_len > text_area_start_x + text_area_width {
                                 y += 1;
                                 x = text_area_start_x;
                             }
                             x += word_len + 1;
                         }
-                        cursor_x = x + user_typed_words[current_word_index].len() as u16;
+                        cursor_x = x + ui::caret_offset(&config, &words_to_type[current_word_index], typed_before_caret);
                         cursor_y = y;
                     }
                 };
 
-                stdout
-                    .execute(cursor::MoveTo(cursor_x, cursor_y))?
-                    .execute(cursor::Show)?;
-
-                if event::poll(std::time::Duration::from_millis(50))? {
-                    if let Event::Key(key_event) = event::read()? {
-                        match key_event.code {
-                            KeyCode::Char(' ') => {
-                                if current_word_index < words_to_type.len() - 1 {
-                                    current_word_index += 1;
-
-                                    if let config::GameMode::Time = game_mode {
-                                        if words_to_type.len() - current_word_index < 10 {
-                                            let mut new_words: Vec<String> = current_word_list.choose_multiple(&mut rng, 20).cloned().collect();
-                                            words_to_type.append(&mut new_words);
-                                            user_typed_words.resize(words_to_type.len(), String::new());
-                                        }
-                                    }
-                                }
+                // The overlay shows the previous frame's timing, as this one isn't written yet.
+                if debug {
+                    let overlay = debug_stats.text(last_keypress.elapsed());
+                    let overlay_x = width.saturating_sub(overlay.len() as u16 + 1);
+                    frame.print(overlay_x, height.saturating_sub(1), &overlay, Style::default());
+                }
+                frame.render(&mut stdout, &config, previous_frame.as_ref())?;
+                previous_frame = Some(frame);
+                debug_stats.frame = frame_start.elapsed();
+
+                // Blink the caret while idle so it's easy to find, but keep it solid while typing.
+                let idle_ms = last_keypress.elapsed().as_millis();
+                let caret_visible = !config.caret_blink
+                    || idle_ms < CARET_IDLE_MS
+                    || (idle_ms / CARET_BLINK_MS).is_multiple_of(2);
+                stdout.execute(cursor::MoveTo(cursor_x, cursor_y))?;
+                if caret_visible {
+                    stdout.execute(cursor::Show)?;
+                } else {
+                    stdout.execute(cursor::Hide)?;
+                }
+
+                // Pasted text is dropped unless `allow_paste` is set, in which case it is
+                // fed in one character at a time as if typed.
+                let key_code = if let Some(code) = pasted.pop_front() {
+                    Some(code)
+                } else if debug_stats.record_poll(event::poll(std::time::Duration::from_millis(50))?) {
+                    match event::read()? {
+                        Event::Key(key_event) => Some(key_event.code),
+                        Event::Paste(text) if config.allow_paste => {
+                            pasted.extend(text.chars().map(|c| {
+                                KeyCode::Char(if c.is_whitespace() { ' ' } else { c })
+                            }));
+                            pasted.pop_front()
+                        }
+                        Event::FocusLost if config.pause_on_blur => {
+                            focused = false;
+                            if start_time.is_some() {
+                                paused_at = Some(Instant::now());
                             }
-                            KeyCode::Char(c) => {
-                                if start_time.is_none() {
-                                    start_time = Some(Instant::now());
-                                }
-                                user_typed_words[current_word_index].push(c);
-                                if let config::GameMode::Words = game_mode {
-                                    if current_word_index == num_words - 1
-                                        && user_typed_words[current_word_index]
-                                            == words_to_type[current_word_index]
-                                    {
-                                        break;
-                                    }
-                                }
+                            None
+                        }
+                        Event::FocusGained => {
+                            focused = true;
+                            None
+                        }
+                        _ => None,
+                    }
+                } else {
+                    None
+                };
+                if let Some(key_code) = key_code {
+                    last_keypress = Instant::now();
+                    let action = bindings.action(key_code);
+
+                    // Waiting for the start trigger: typing is ignored, and the trigger itself
+                    // only starts the clock without being typed.
+                    let trigger = match config.start_trigger {
+                        config::StartTrigger::FirstKey => None,
+                        config::StartTrigger::Space => Some(KeyCode::Char(' ')),
+                        config::StartTrigger::Enter => Some(KeyCode::Enter),
+                    };
+                    if let Some(trigger) = trigger
+                        && start_time.is_none()
+                        && matches!(key_code, KeyCode::Char(_) | KeyCode::Enter)
+                        && matches!(action, None | Some(keys::Action::Advance))
+                    {
+                        if key_code == trigger {
+                            start_time = Some(Instant::now());
+                            word_start = start_time;
+                        }
+                        continue;
+                    }
+
+                    // Enter only advances to the next word when `enter_advances` is set.
+                    let advances = match (action, key_code) {
+                        (Some(keys::Action::Advance), _) => true,
+                        (None, KeyCode::Enter) => config.enter_advances,
+                        _ => false,
+                    };
+                    // The space typed out of habit right after an automatic advance.
+                    let redundant_space = advances && auto_advanced;
+                    auto_advanced = false;
+                    let mut advance_word = false;
+                    match key_code {
+                        _ if advances && !redundant_space && current_word_index < words_to_type.len() - 1 => {
+                            advance_word = true;
+                        }
+                        // The advance key on the last word, or Space or Enter when they don't
+                        // advance, is ignored rather than typed into the word.
+                        _ if advances => {}
+                        _ if action == Some(keys::Action::Restart) && config.restart_button => {
+                            // Restart the test, with new words unless it is a replay.
+                            if replay.is_none() {
+                                seed = rng.r#gen();
+                            }
+                            word_rng = StdRng::seed_from_u64(seed);
+                            words_to_type = match fixed_words {
+                                Some(words) => words.clone(),
+                                None => sampler.build(pool_size, &mut word_rng),
+                            };
+                            user_typed_words = vec![String::new(); words_to_type.len()];
+                            word_times = vec![None; words_to_type.len()];
+                            current_word_index = 0;
+                            caret_back = 0;
+                            start_time = None;
+                            word_start = None;
+                            word_corrections = 0;
+                            combo = 0;
+                            max_combo = 0;
+                            pasted.clear();
+                            timeline.clear();
+                            last_wpm_update = None;
+                            wpm = 0.0;
+                            kpm = 0.0;
+                        }
+                        _ if action == Some(keys::Action::Quit) => {
+                            aborted = true;
+                            break; // Exit test and go to results screen
+                        }
+                        KeyCode::Char(' ') | KeyCode::Enter => {}
+                        KeyCode::Char(c) => {
+                            if start_time.is_none() {
+                                start_time = Some(Instant::now());
+                                word_start = start_time;
+                            }
+                            let mistakes_before = scoring::word_totals(
+                                &user_typed_words[current_word_index],
+                                &words_to_type[current_word_index],
+                                case_insensitive,
+                                &config.optional_chars,
+                            )
+                            .1;
+                            let typed = &mut user_typed_words[current_word_index];
+                            graphemes::insert(typed, graphemes::count(typed) - caret_back, c);
+                            if config.beep_on_error
+                                && scoring::word_totals(
+                                    &user_typed_words[current_word_index],
+                                    &words_to_type[current_word_index],
+                                    case_insensitive,
+                                    &config.optional_chars,
+                                )
+                                .1 > mistakes_before
+                            {
+                                stdout.execute(Print('\x07'))?;
                             }
-                            KeyCode::Backspace => {
-                                user_typed_words[current_word_index].pop();
+                            let completed = scoring::words_match(
+                                &user_typed_words[current_word_index],
+                                &words_to_type[current_word_index],
+                                case_insensitive,
+                                &config.optional_chars,
+                            );
+                            // A challenge also ends with its text, even in time mode.
+                            if (matches!(game_mode, config::GameMode::Words) || fixed_words.is_some())
+                                && current_word_index == num_words - 1
+                                && completed
+                            {
+                                word_times[current_word_index] =
+                                    word_start.map(|started| started.elapsed().as_secs_f64());
+                                combo += 1;
+                                max_combo = max_combo.max(combo);
+                                break;
                             }
-                            KeyCode::Tab => {
-                                if config.restart_button {
-                                    // Restart the test
-                                    words_to_type = current_word_list
-                                        .choose_multiple(&mut rng, num_words)
-                                        .cloned()
-                                        .collect();
-                                    user_typed_words = vec![String::new(); words_to_type.len()];
-                                    current_word_index = 0;
-                                    start_time = None;
-                                    last_wpm_update = None;
-                                    wpm = 0.0;
+                            if config.auto_advance_on_correct
+                                && completed
+                                && current_word_index < words_to_type.len() - 1
+                            {
+                                advance_word = true;
+                                auto_advanced = true;
+                            }
+                        }
+                        KeyCode::Backspace | KeyCode::Delete => {
+                            let typed = &mut user_typed_words[current_word_index];
+                            let caret = graphemes::count(typed) - caret_back;
+                            // Backspace removes the character before the caret, Delete the one after it.
+                            let removed = match key_code {
+                                KeyCode::Backspace if caret > 0 => graphemes::remove(typed, caret - 1),
+                                KeyCode::Delete if caret_back > 0 => {
+                                    caret_back -= 1;
+                                    graphemes::remove(typed, caret)
                                 }
+                                _ => false,
+                            };
+                            if removed {
+                                word_corrections += 1;
                             }
-                            KeyCode::Esc => {
-                                break; // Exit test and go to results screen
-                            },
-                            _ => {}
+                            if config
+                                .max_corrections_per_word
+                                .is_some_and(|max| word_corrections > max)
+                            {
+                                failed = true;
+                                break;
+                            }
+                        }
+                        KeyCode::Left => {
+                            caret_back = (caret_back + 1).min(graphemes::count(&user_typed_words[current_word_index]));
+                        }
+                        KeyCode::Right => caret_back = caret_back.saturating_sub(1),
+                        KeyCode::Home => caret_back = graphemes::count(&user_typed_words[current_word_index]),
+                        KeyCode::End => caret_back = 0,
+                        _ => {}
+                    }
+                    if advance_word {
+                        if let Some(started) = word_start {
+                            word_times[current_word_index] = Some(started.elapsed().as_secs_f64());
+                        }
+                        word_start = Some(Instant::now());
+                        word_corrections = 0;
+                        caret_back = 0;
+                        if scoring::words_match(
+                            &user_typed_words[current_word_index],
+                            &words_to_type[current_word_index],
+                            case_insensitive,
+                            &config.optional_chars,
+                        ) {
+                            combo += 1;
+                            max_combo = max_combo.max(combo);
+                        } else {
+                            combo = 0;
+                        }
+                        current_word_index += 1;
+
+                        if let config::GameMode::Time = game_mode
+                            && fixed_words.is_none()
+                            && words_to_type.len() - current_word_index < 10
+                        {
+                            sampler.extend(&mut words_to_type, 20, &mut word_rng);
+                            user_typed_words.resize(words_to_type.len(), String::new());
+                            word_times.resize(words_to_type.len(), None);
                         }
                     }
                 }
@@ -471,94 +1101,762 @@ fn main() -> io::Result<()> {
                 }
             }
 
+            // A full timed test is scored over its whole length, grace or not.
+            let elapsed = start_time.map_or(0.0, |s| {
+                scoring::with_first_key_grace(s.elapsed().as_secs_f64(), config.first_key_grace_ms)
+            });
             let duration = match game_mode {
+                // A timed challenge can be finished before the time runs out.
+                config::GameMode::Time if endless => elapsed,
+                config::GameMode::Time if fixed_words.is_some() => elapsed.min(time_limit as f64),
                 config::GameMode::Time => time_limit as f64,
-                config::GameMode::Words => start_time.map_or(0.0, |s| s.elapsed().as_secs_f64()),
+                config::GameMode::Words => elapsed,
             };
 
-            let (correct_chars_total, incorrect_chars_total) = user_typed_words
-                .iter()
-                .zip(words_to_type.iter())
-                .take(current_word_index + 1)
-                .fold((0, 0), |(mut c, mut i), (typed, original)| {
-                    for (tc, oc) in typed.chars().zip(original.chars()) {
-                        if tc == oc {
-                            c += 1;
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    if typed.len() > original.len() {
-                        i += typed.len() - original.len();
-                    }
-                    (c, i)
-                });
+            let scored_words = scoring::scored_words(&user_typed_words, current_word_index);
 
-            let final_wpm = if duration > 0.0 {
-                (correct_chars_total as f64 / 5.0) / (duration / 60.0)
-            } else {
-                0.0
-            };
+            let (correct_chars_total, _) = scoring::char_totals(
+                &user_typed_words,
+                &words_to_type,
+                scored_words,
+                case_insensitive,
+                &config.optional_chars,
+            );
+
+            let final_wpm = scoring::wpm(correct_chars_total, duration);
+
+            let accuracy = scoring::accuracy(
+                config.accuracy_mode,
+                &user_typed_words,
+                &words_to_type,
+                scored_words,
+                case_insensitive,
+                &config.optional_chars,
+            );
 
-            let accuracy = if (correct_chars_total + incorrect_chars_total) == 0 {
-                100.0
+            let not_counted = result_counts(final_wpm, failed, config.max_saved_wpm);
+
+            if not_counted.is_none() {
+                session_wpms.push(final_wpm);
+            }
+
+            // Why this result stays out of the per-mode stats, if it does.
+            let not_saved_reason = if no_save {
+                Some("saving is disabled")
+            } else if persistence_error.is_some() {
+                Some("running without persistence")
+            } else if not_counted.is_some() {
+                not_counted
+            } else if drill {
+                Some("quick drill")
+            } else if challenge.is_some() {
+                Some("challenge")
+            } else if alphabet {
+                Some("warm-up")
+            } else if practice_weak {
+                Some("practice run")
             } else {
-                (correct_chars_total as f64 / (correct_chars_total + incorrect_chars_total) as f64)
-                    * 100.0
+                None
             };
 
-            if final_wpm >= 5.0 {
-                let test_result = config::TestResult {
-                    wpm: final_wpm,
-                    accuracy,
-                    timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            // Playlists and flow sessions list every test that counted, numbered in order.
+            if (flow_test.is_some() || (playlist.is_some() && !drill)) && not_counted.is_none() {
+                // The label names the difficulty the result is saved under; flow tests vary
+                // it, so Medium is named too.
+                let label = match (&flow_test, mode_info.difficulty) {
+                    (Some(_), config::WordListDifficulty::Medium) => format!("{} medium", mode_info.label()),
+                    _ => mode_info.label(),
                 };
+                recap_lines.push(recap_line(&config, recap_lines.len() + 1, &label, final_wpm, accuracy));
+            }
+            // Quick drills are throwaway practice and leave no trace at all.
+            if !no_save && !drill && !alphabet && persistence_error.is_none() && not_counted.is_none() {
+                practice::record_weak_words(
+                    &mut config.weak_words,
+                    &words_to_type,
+                    &user_typed_words,
+                    &word_times,
+                    case_insensitive,
+                    &config.optional_chars,
+                );
 
-                let key = match config.game_mode {
-                    config::GameMode::Words => format!("words_{}_{}", config.default_test_length, config.selected_language),
-                    config::GameMode::Time => format!("time_{}_{}", config.default_time_limit, config.selected_language),
-                };
-                config.results.entry(key).or_insert_with(Vec::new).push(test_result);
+                // Practice runs only feed the weak-word tally, not the per-mode stats.
+                if not_saved_reason.is_none() {
+                    let test_result = config::TestResult {
+                        wpm: final_wpm,
+                        accuracy,
+                        max_combo,
+                        accuracy_mode: config.accuracy_mode,
+                        mode: Some(mode_info.clone()),
+                        seed: Some(seed),
+                        word_settings: Some(word_settings.clone()),
+                        timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    };
+
+                    config.results.entry(key.clone()).or_default().push(test_result);
+                }
                 config::save_config(&config)?;
             }
 
+            // Esc is how an endless test is meant to end, so it still counts as finishing.
+            if config.chime_on_complete && (!aborted || endless) && !failed && running.load(Ordering::SeqCst) {
+                stdout.execute(Print('\x07'))?;
+            }
+            ui::apply_ui_colors(&mut stdout, &config)?;
             stdout.execute(terminal::Clear(terminal::ClearType::All))?;
-            let results = vec![
-                "Typing test complete!".to_string(),
-                format!("WPM: {:.2}", final_wpm),
-                format!("Accuracy: {:.2}%", accuracy),
-                "".to_string(),
-                "Press 'Tab' to restart or 'Esc' to exit.".to_string(),
+            let mut results: Vec<ui::Line> = vec![
+                if failed {
+                    "Test failed: too many corrections on one word.".to_string().into()
+                } else {
+                    "Typing test complete!".to_string().into()
+                },
+                format!("WPM: {}", ui::format_decimal(&config, final_wpm, 2)).into(),
             ];
+            if let Some(challenge) = challenge.as_ref().filter(|_| !drill) {
+                if !failed && challenge.passed(final_wpm, accuracy) {
+                    results.push(ui::Line::colored("Challenge passed!".to_string(), Color::Green));
+                } else {
+                    let line = format!("Challenge failed: {} needed.", challenge.requirement());
+                    results.push(ui::Line::colored(line, Color::Red));
+                }
+            }
+            if let Some(average) = recent_average {
+                let delta = final_wpm - average;
+                let sign = if delta >= 0.0 { "+" } else { "" };
+                let line = format!(
+                    "{}{} vs your recent avg ({})",
+                    sign,
+                    ui::format_decimal(&config, delta, 1),
+                    ui::format_decimal(&config, average, 1)
+                );
+                results.push(ui::Line::colored(line, if delta >= 0.0 { Color::Green } else { Color::Red }));
+            }
+            if let Some(target_wpm) = config.target_wpm {
+                let bar = format!(
+                    "Goal: │{}│ {:.0}/{:.0}",
+                    ui::render_bar(final_wpm / target_wpm, GOAL_BAR_WIDTH),
+                    final_wpm,
+                    target_wpm
+                );
+                if final_wpm >= target_wpm {
+                    results.push(ui::Line::colored(bar, Color::Green));
+                } else {
+                    results.push(bar.into());
+                }
+            }
+            let final_kpm = if duration > 0.0 {
+                scoring::keystrokes(&user_typed_words, scored_words) as f64 / (duration / 60.0)
+            } else {
+                0.0
+            };
+            if config.show_kpm {
+                results.push(format!("KPM: {:.0}", final_kpm).into());
+            }
+            let final_cpm = if duration > 0.0 {
+                correct_chars_total as f64 / (duration / 60.0)
+            } else {
+                0.0
+            };
+            results.push(format!("Correct characters: {} ({:.0} CPM)", correct_chars_total, final_cpm).into());
+            results.extend([
+                match config.accuracy_mode {
+                    config::AccuracyMode::Character => {
+                        format!("Accuracy: {}% (per character)", ui::format_decimal(&config, accuracy, 2))
+                    }
+                    config::AccuracyMode::WordAverage => {
+                        format!("Accuracy: {}% (word average)", ui::format_decimal(&config, accuracy, 2))
+                    }
+                },
+                format!("Best combo: {} words", max_combo),
+                match game_mode {
+                    config::GameMode::Words => format!("Time: {}s", ui::format_decimal(&config, duration, 1)),
+                    config::GameMode::Time if endless => format!("Words: {} in {:.0}s", current_word_index, duration),
+                    config::GameMode::Time => format!("Words: {}", current_word_index),
+                },
+            ].map(ui::Line::from));
+            // An endless test is split up over as long as it actually ran.
+            let pace_span = if endless { duration as usize } else { time_limit as usize };
+            let segment_secs = if endless {
+                pace_span.div_ceil(MAX_PACE_SEGMENTS).next_multiple_of(PACE_SEGMENT_SECS)
+            } else {
+                PACE_SEGMENT_SECS
+            };
+            if let config::GameMode::Time = game_mode
+                && pace_span >= 2 * PACE_SEGMENT_SECS
+            {
+                let segments = scoring::segment_wpms(
+                    &timeline,
+                    correct_chars_total,
+                    segment_secs,
+                    pace_span,
+                );
+                let pace: Vec<String> = segments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, wpm)| {
+                        let start = i * segment_secs;
+                        let end = (start + segment_secs).min(pace_span);
+                        format!("{}-{}s: {:.0}", start, end, wpm)
+                    })
+                    .collect();
+                results.push(format!("Pace: {}", pace.join(" | ")).into());
+            }
+            if not_counted == Some("implausible") {
+                results.push(ui::Line::colored("Result rejected (implausible)".to_string(), Color::Red));
+            } else if let Some(reason) = not_saved_reason {
+                results.push(format!("Result not saved: {}", reason).into());
+            }
+            // Only finished words; the one in progress when time ran out isn't a mistake.
+            let missed: Vec<(&String, &String)> = user_typed_words
+                .iter()
+                .zip(words_to_type.iter())
+                .take(current_word_index)
+                .filter(|(typed, target)| {
+                    !scoring::words_match(typed, target, case_insensitive, &config.optional_chars)
+                })
+                .collect();
+            let mut wrong_words: Vec<String> = Vec::new();
+            for (_, target) in &missed {
+                if !wrong_words.contains(target) {
+                    wrong_words.push(target.to_string());
+                }
+            }
+            // Plain text for the exported report, colored lines for the review on screen.
+            let (mistakes, mistake_lines): (Vec<String>, Vec<ui::Line>) = missed
+                .iter()
+                .map(|(typed, target)| {
+                    let expected = scoring::resolve_optional(typed, target, case_insensitive, &config.optional_chars);
+                    let ops = scoring::align_word(typed, &expected, case_insensitive);
+                    (format!("{}: {}", target, ui::format_diff(&ops)), ui::diff_line(&config, target, &ops))
+                })
+                .unzip();
+            if !mistakes.is_empty() {
+                results.push("".to_string().into());
+                results.push("Mistakes ([x>y] typed x for y, [+x] extra, [-y] missed):".to_string().into());
+                results.extend(mistake_lines.into_iter().take(MAX_REVIEW_WORDS));
+            }
 
-            let (width, height) = terminal::size()?;
-            for (i, line) in results.iter().enumerate() {
-                let x = (width.saturating_sub(line.len() as u16)) / 2;
-                let y = (height / 2) + i as u16;
-                stdout.execute(cursor::MoveTo(x, y))?.execute(Print(line))?;
+            // Finished words colored by how fast each was typed, relative to the rest.
+            let speeds: Vec<(&String, f64)> = words_to_type
+                .iter()
+                .zip(scoring::word_wpms(&words_to_type, &word_times))
+                .take(current_word_index + 1)
+                .filter_map(|(word, wpm)| wpm.map(|wpm| (word, wpm)))
+                .collect();
+            if speeds.len() >= 2 {
+                let slowest = speeds.iter().map(|(_, wpm)| *wpm).fold(f64::INFINITY, f64::min);
+                let fastest = speeds.iter().map(|(_, wpm)| *wpm).fold(0.0, f64::max);
+                let (width, _) = terminal::size()?;
+                let max_width = (width as usize).saturating_sub(10).clamp(20, 80);
+
+                let mut lines: Vec<ui::Line> = Vec::new();
+                let mut current = ui::Line { spans: Vec::new() };
+                let mut current_width = 0;
+                for (word, wpm) in speeds {
+                    let word_width = graphemes::count(word) + 1;
+                    if current_width + word_width > max_width && !current.spans.is_empty() {
+                        lines.push(std::mem::replace(&mut current, ui::Line { spans: Vec::new() }));
+                        current_width = 0;
+                    }
+                    let fraction = if fastest > slowest { (wpm - slowest) / (fastest - slowest) } else { 1.0 };
+                    current.spans.push((format!("{} ", word), Some(ui::gradient_color(fraction))));
+                    current_width += word_width;
+                }
+                lines.push(current);
+
+                results.push("".to_string().into());
+                results.push("Word speeds (red slow, green fast):".to_string().into());
+                results.extend(lines.into_iter().take(MAX_SPEED_LINES));
             }
+            results.extend([
+                "".to_string(),
+                if ends_session {
+                    format!(
+                        "Press 'x' to export a report, 'w' to drill mistakes, 's' for stats, or '{}' or '{}' to exit.",
+                        restart_key, quit_key
+                    )
+                } else {
+                    format!(
+                        "Press 'x' to export a report, 'w' to drill mistakes, 's' for stats, '{}' to restart or '{}' to exit.",
+                        restart_key, quit_key
+                    )
+                },
+            ].map(ui::Line::from));
+            ui::draw_centered_lines(&mut stdout, &config, &results)?;
 
+            let report = report::TestReport {
+                mode: mode_info.label(),
+                wpm: final_wpm,
+                raw_wpm: final_kpm / 5.0,
+                accuracy,
+                consistency: scoring::consistency(&words_to_type, &word_times),
+                duration,
+                mistakes,
+            };
+            let mut exported = false;
+
+            // With auto-advance the next test starts when the countdown runs out.
+            let mut next_test_at = config
+                .auto_next_secs
+                .filter(|_| !ends_session)
+                .map(|secs| Instant::now() + std::time::Duration::from_secs(secs));
             loop {
+                if let Some(deadline) = next_test_at {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        break;
+                    }
+                    let (width, height) = terminal::size()?;
+                    let countdown = format!(
+                        "Next test in {}s, '{}' to stop.",
+                        remaining.as_secs_f64().ceil() as u64,
+                        quit_key
+                    );
+                    stdout
+                        .execute(cursor::MoveTo(0, height.saturating_sub(2)))?
+                        .execute(terminal::Clear(terminal::ClearType::CurrentLine))?
+                        .execute(cursor::MoveTo(ui::centered_x(width, &countdown), height.saturating_sub(2)))?
+                        .execute(Print(countdown))?;
+                    if !event::poll(remaining.min(std::time::Duration::from_millis(200)))? {
+                        continue;
+                    }
+                }
                 if let Event::Key(key_event) = event::read()? {
                     match key_event.code {
-                        KeyCode::Tab => {
+                        key if bindings.action(key) == Some(keys::Action::Restart) && !ends_session => {
                             break;
                         }
-                        KeyCode::Esc => {
+                        key if matches!(bindings.action(key), Some(keys::Action::Restart | keys::Action::Quit)) => {
                             running.store(false, Ordering::SeqCst);
                             break;
                         }
+                        KeyCode::Char('x') if !exported => {
+                            let message = match report.save() {
+                                Ok(path) => {
+                                    exported = true;
+                                    format!("Report saved to {}", path.display())
+                                }
+                                Err(e) => format!("Could not save the report: {}", e),
+                            };
+                            results.push(message.into());
+                            ui::draw_centered_lines(&mut stdout, &config, &results)?;
+                        }
+                        KeyCode::Char('w') if !wrong_words.is_empty() => {
+                            drill_words = Some(wrong_words);
+                            break;
+                        }
+                        KeyCode::Char('w') => {
+                            results.push("Nothing to drill — nice!".to_string().into());
+                            ui::draw_centered_lines(&mut stdout, &config, &results)?;
+                        }
+                        KeyCode::Char('s') => {
+                            let outcome = stats::browse(&mut stdout, &mut config, !no_save && persistence_error.is_none())?;
+                            if let Some(card) = outcome.card {
+                                card_to_print = Some(card);
+                            }
+                            match outcome.replay {
+                                Some(_) if fixed_text.is_some() => {
+                                    results.push("Can't replay a result during a challenge or warm-up.".to_string().into());
+                                }
+                                Some(replay) if let Some(problem) = replay_problem(&config, &replay) => {
+                                    results.push(format!("Can't replay this result: {}.", problem).into());
+                                }
+                                Some(replay) => {
+                                    replay_next = Some(replay);
+                                    break;
+                                }
+                                None => {}
+                            }
+                            // Coming back from the stats shouldn't start the next test right away.
+                            next_test_at = None;
+                            ui::apply_ui_colors(&mut stdout, &config)?;
+                            stdout.execute(terminal::Clear(terminal::ClearType::All))?;
+                            ui::draw_centered_lines(&mut stdout, &config, &results)?;
+                        }
                         _ => {}
                     }
                 }
             }
+            // A drill or replay slots in after a playlist test without using up the next one.
+            if drill_words.is_none() && replay_next.is_none() {
+                playlist_index += 1;
+            }
             Ok(())
         })() {
             Ok(_) => {},
             Err(e) => return Err(e),
         }
     }
-    terminal::disable_raw_mode()?;
-    stdout.execute(LeaveAlternateScreen)?;
+    if !session_wpms.is_empty() && !single_run {
+        show_session_summary(&mut stdout, &config, &session_wpms, &recap_lines)?;
+    }
+
+    stdout.execute(ResetColor)?.execute(cursor::Show)?;
+    terminal::disable_raw_mode().map_err(TypingTestError::Terminal)?;
+    if config.pause_on_blur {
+        stdout.execute(DisableFocusChange).map_err(TypingTestError::Terminal)?;
+    }
+    stdout
+        .execute(DisableBracketedPaste)
+        .and_then(|stdout| stdout.execute(LeaveAlternateScreen))
+        .map_err(TypingTestError::Terminal)?;
+    if let Some(card) = card_to_print {
+        println!("{}", card);
+    }
+    Ok(())
+}
+
+/// Timings shown by `--debug`: how long the last frame took to draw, and how many polls
+/// over the last second found an event waiting.
+struct DebugStats {
+    frame: std::time::Duration,
+    polls: u32,
+    events: u32,
+    window_start: Instant,
+    events_per_poll: f64,
+}
+
+impl DebugStats {
+    fn new() -> Self {
+        Self {
+            frame: std::time::Duration::ZERO,
+            polls: 0,
+            events: 0,
+            window_start: Instant::now(),
+            events_per_poll: 0.0,
+        }
+    }
+
+    /// Counts one poll and passes its result through.
+    fn record_poll(&mut self, ready: bool) -> bool {
+        self.polls += 1;
+        self.events += ready as u32;
+        if self.window_start.elapsed().as_secs() >= 1 {
+            self.events_per_poll = self.events as f64 / self.polls as f64;
+            self.polls = 0;
+            self.events = 0;
+            self.window_start = Instant::now();
+        }
+        ready
+    }
+
+    fn text(&self, since_key: std::time::Duration) -> String {
+        format!(
+            "frame {:.1}ms | events/poll {:.2} | last key {}ms",
+            self.frame.as_secs_f64() * 1000.0,
+            self.events_per_poll,
+            since_key.as_millis()
+        )
+    }
+}
+
+/// Why a finished test doesn't count, if it doesn't. A test that doesn't count is kept
+/// out of everything: stats, weak words, the session averages and the recap.
+fn result_counts(wpm: f64, failed: bool, max_saved_wpm: Option<f64>) -> Option<&'static str> {
+    if failed {
+        Some("too many corrections")
+    } else if wpm < MIN_COUNTED_WPM {
+        Some("WPM too low")
+    } else if max_saved_wpm.is_some_and(|max| wpm > max) {
+        // Results this fast are almost always pasted text or a bug.
+        Some("implausible")
+    } else {
+        None
+    }
+}
+
+/// One test on the session recap, e.g. "2. words 25 english: 81.50 WPM, 97.5% accuracy".
+fn recap_line(config: &config::Config, number: usize, label: &str, wpm: f64, accuracy: f64) -> String {
+    format!(
+        "{}. {}: {} WPM, {}% accuracy",
+        number,
+        label,
+        ui::format_decimal(config, wpm, 2),
+        ui::format_decimal(config, accuracy, 1)
+    )
+}
+
+/// Why `replay` wouldn't get the words it was saved with, if it wouldn't: its language
+/// pack is gone or has changed, or a setting that decides the words is different now.
+fn replay_problem(config: &config::Config, replay: &stats::Replay) -> Option<String> {
+    let language = &replay.mode.language;
+    let Some(fingerprint) = words::language_fingerprint(&config.language_packs, language) else {
+        return Some(format!("the '{}' language pack isn't installed", language));
+    };
+    let saved = &replay.settings;
+    let changed = [
+        (saved.restrict_charset != config.restrict_charset, "restrict_charset"),
+        (saved.frequency_weighted != config.frequency_weighted, "frequency_weighted"),
+        (saved.split_on_hyphen != config.split_on_hyphen, "split_on_hyphen"),
+    ];
+    match changed.iter().find(|(changed, _)| *changed) {
+        Some((_, setting)) => Some(format!("{} has changed since it was saved", setting)),
+        None if saved.list_fingerprint != fingerprint => {
+            Some(format!("the '{}' word list has changed since it was saved", language))
+        }
+        None => None,
+    }
+}
+
+/// Random settings for the next `--flow` test within `bounds`, and the difficulty to
+/// play it at.
+fn pick_flow_test(bounds: &config::FlowBounds, rng: &mut impl Rng) -> (RunOverrides, config::WordListDifficulty) {
+    let game_mode = bounds.game_modes.choose(rng).cloned().unwrap_or(config::GameMode::Words);
+    let overrides = match game_mode {
+        config::GameMode::Words => RunOverrides {
+            game_mode: Some(game_mode),
+            test_length: Some(pick_multiple_of_five(rng, bounds.min_words as u64, bounds.max_words as u64) as usize),
+            time_limit: None,
+        },
+        config::GameMode::Time => RunOverrides {
+            game_mode: Some(game_mode),
+            test_length: None,
+            time_limit: Some(pick_multiple_of_five(rng, bounds.min_time, bounds.max_time)),
+        },
+    };
+    let difficulty = bounds.difficulties.choose(rng).copied().unwrap_or_default();
+    (overrides, difficulty)
+}
+
+/// A random multiple of five from `min` to `max`, at least five. A `max` below `min`
+/// is treated as `min`.
+fn pick_multiple_of_five(rng: &mut impl Rng, min: u64, max: u64) -> u64 {
+    let low = min.div_ceil(5).max(1);
+    let high = (max / 5).max(low);
+    rng.gen_range(low..=high) * 5
+}
+
+/// Test settings given on the command line for a single run, or by the schedule. They
+/// apply on top of the loaded config and are never saved.
+struct RunOverrides {
+    game_mode: Option<config::GameMode>,
+    test_length: Option<usize>,
+    time_limit: Option<u64>,
+}
+
+impl RunOverrides {
+    /// `--length` implies words mode and `--time` implies time mode unless `--mode`
+    /// says otherwise.
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let test_length = match arg_value(args, "--length") {
+            Some(value) => Some(parse_positive::<usize>("--length", value)?),
+            None => None,
+        };
+        let time_limit = match arg_value(args, "--time") {
+            Some(value) => Some(parse_positive::<u64>("--time", value)?),
+            None => None,
+        };
+        let game_mode = match arg_value(args, "--mode") {
+            Some("words") => Some(config::GameMode::Words),
+            Some("time") => Some(config::GameMode::Time),
+            Some(other) => return Err(format!("unknown mode '{}': use 'words' or 'time'", other)),
+            None if time_limit.is_some() => Some(config::GameMode::Time),
+            None if test_length.is_some() => Some(config::GameMode::Words),
+            None => None,
+        };
+        Ok(Self { game_mode, test_length, time_limit })
+    }
+
+    fn is_some(&self) -> bool {
+        self.game_mode.is_some()
+    }
+}
+
+fn parse_positive<T: std::str::FromStr + PartialOrd + Default>(flag: &str, value: &str) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(n) if n > T::default() => Ok(n),
+        _ => Err(format!("{} needs a positive whole number, got '{}'", flag, value)),
+    }
+}
+
+/// Merges the saved results of mode `from` into `into` after asking on stdin.
+fn merge_modes(from: &str, into: &str) -> error::Result<()> {
+    let mut config = config::load_config_light();
+    let Some(count) = config.results.get(from).map(|results| results.len()) else {
+        eprintln!("No results saved under '{}'.", from);
+        return Ok(());
+    };
+    let existing = config.results.get(into).map_or(0, |results| results.len());
+    print!(
+        "Move {} results from '{}' into '{}' ({} there now)? [y/N] ",
+        count, from, into, existing
+    );
+    io::Write::flush(&mut io::stdout())?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+    match config::merge_modes(&mut config, from, into) {
+        Ok(moved) => {
+            config::save_config(&config)?;
+            println!("Moved {} results into '{}'.", moved, into);
+        }
+        Err(e) => eprintln!("{}", e),
+    }
     Ok(())
 }
+
+/// Returns the value following `name` on the command line, e.g. `--flag value`.
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|value| value.as_str())
+}
+
+/// First row of the text box in the Boxes layout: under the stats box, or near the top
+/// with one row left for the ready hint when the stats box is at the bottom.
+fn boxes_main_start_y(config: &config::Config) -> u16 {
+    match config.stats_bar_position {
+        config::StatsBarPosition::Top => 6,
+        config::StatsBarPosition::Bottom => 3,
+    }
+}
+
+/// The hint shown on row `y` until the first keystroke, so a waiting test doesn't look frozen.
+fn draw_ready_hint(frame: &mut frame::Frame, config: &config::Config, width: u16, y: u16) {
+    let hint = match config.start_trigger {
+        config::StartTrigger::FirstKey => "Ready — start typing",
+        config::StartTrigger::Space => "Ready — press Space to start",
+        config::StartTrigger::Enter => "Ready — press Enter to start",
+    };
+    frame.print(ui::centered_x(width, hint), y, hint, Style::fg(Color::DarkGrey));
+}
+
+/// The key guide shown before the very first test.
+fn show_tutorial(stdout: &mut io::Stdout, config: &config::Config, bindings: &keys::Bindings) -> io::Result<()> {
+    let mut lines = vec![
+        "Welcome to Typing Test!".to_string(),
+        String::new(),
+        "Type the words on screen; the timer starts with your first key.".to_string(),
+        format!("'{}' moves to the next word.", keys::key_name(bindings.advance)),
+        "'Backspace' fixes a mistake in the current word.".to_string(),
+    ];
+    if config.restart_button {
+        lines.push(format!("'{}' restarts the test.", keys::key_name(bindings.restart)));
+    }
+    lines.extend([
+        format!("'{}' finishes the session.", keys::key_name(bindings.quit)),
+        String::new(),
+        "Run with -m to change settings and -s to see your stats.".to_string(),
+        String::new(),
+        "Press any key to start.".to_string(),
+    ]);
+    let lines: Vec<ui::Line> = lines.into_iter().map(ui::Line::from).collect();
+    ui::draw_centered_lines(stdout, config, &lines)?;
+
+    loop {
+        if let Event::Key(_) = event::read()? {
+            ui::apply_ui_colors(stdout, config)?;
+            stdout.execute(terminal::Clear(terminal::ClearType::All))?;
+            return Ok(());
+        }
+    }
+}
+
+/// `details` are extra lines listed under the totals, e.g. one per playlist test.
+fn show_session_summary(
+    stdout: &mut io::Stdout,
+    config: &config::Config,
+    session_wpms: &[f64],
+    details: &[String],
+) -> io::Result<()> {
+    let average = session_wpms.iter().sum::<f64>() / session_wpms.len() as f64;
+    let best = session_wpms.iter().cloned().fold(0.0, f64::max);
+
+    ui::apply_ui_colors(stdout, config)?;
+    stdout.execute(terminal::Clear(terminal::ClearType::All))?;
+    let mut lines = vec![
+        "Session complete!".to_string(),
+        format!("Tests completed: {}", session_wpms.len()),
+        format!("Average WPM: {}", ui::format_decimal(config, average, 2)),
+        format!("Best WPM: {}", ui::format_decimal(config, best, 2)),
+    ];
+    if !details.is_empty() {
+        lines.push("".to_string());
+        // A long flow session lists only its latest tests.
+        let hidden = details.len().saturating_sub(MAX_RECAP_LINES);
+        if hidden > 0 {
+            lines.push(format!("... {} earlier tests", hidden));
+        }
+        lines.extend(details[hidden..].iter().cloned());
+    }
+    lines.extend(["".to_string(), "Press any key to exit.".to_string()]);
+    let lines: Vec<ui::Line> = lines.into_iter().map(ui::Line::from).collect();
+    ui::draw_centered_lines(stdout, config, &lines)?;
+
+    loop {
+        if let Event::Key(_) = event::read()? {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack(name: &str, words: &[&str]) -> config::LanguagePack {
+        config::LanguagePack {
+            name: name.to_string(),
+            words: words.iter().map(|w| w.to_string()).collect(),
+            frequencies: None,
+            path: None,
+        }
+    }
+
+    fn replay_of(config: &config::Config) -> stats::Replay {
+        stats::Replay {
+            mode: config::ModeInfo::from_key("words_25_english").unwrap(),
+            seed: 1,
+            settings: config::WordSettings {
+                restrict_charset: config.restrict_charset,
+                frequency_weighted: config.frequency_weighted,
+                split_on_hyphen: config.split_on_hyphen,
+                list_fingerprint: words::language_fingerprint(&config.language_packs, "english").unwrap(),
+            },
+        }
+    }
+
+    #[test]
+    fn replay_with_unchanged_settings_and_pack_is_allowed() {
+        let config = config::Config { language_packs: vec![pack("english", &["the", "cat"])], ..Default::default() };
+        assert_eq!(replay_problem(&config, &replay_of(&config)), None);
+    }
+
+    #[test]
+    fn replay_is_refused_when_the_words_would_differ() {
+        let mut config = config::Config { language_packs: vec![pack("english", &["the", "cat"])], ..Default::default() };
+        let replay = replay_of(&config);
+
+        config.split_on_hyphen = !config.split_on_hyphen;
+        assert!(replay_problem(&config, &replay).unwrap().contains("split_on_hyphen"));
+        config.split_on_hyphen = !config.split_on_hyphen;
+
+        config.language_packs = vec![pack("english", &["the", "dog"])];
+        assert!(replay_problem(&config, &replay).unwrap().contains("word list has changed"));
+
+        config.language_packs = Vec::new();
+        assert!(replay_problem(&config, &replay).unwrap().contains("isn't installed"));
+    }
+
+    #[test]
+    fn only_plausible_finished_results_count() {
+        assert_eq!(result_counts(80.0, false, Some(300.0)), None);
+        assert_eq!(result_counts(80.0, false, None), None);
+        assert_eq!(result_counts(80.0, true, Some(300.0)), Some("too many corrections"));
+        assert_eq!(result_counts(4.0, false, Some(300.0)), Some("WPM too low"));
+        assert_eq!(result_counts(350.0, false, Some(300.0)), Some("implausible"));
+        assert_eq!(result_counts(350.0, false, None), None);
+    }
+
+    #[test]
+    fn recap_line_uses_the_decimal_separator() {
+        let mut config = config::Config::default();
+        assert_eq!(recap_line(&config, 2, "words 25 english", 81.5, 97.54), "2. words 25 english: 81.50 WPM, 97.5% accuracy");
+        config.decimal_separator = ',';
+        assert_eq!(recap_line(&config, 1, "time 30 english", 60.0, 100.0), "1. time 30 english: 60,00 WPM, 100,0% accuracy");
+    }
+}
@@ -1,8 +1,10 @@
-use crate::config::{self, Config, GameMode, LayoutTheme};
+use crate::config::{self, Config, GameMode, LayoutTheme, StatsBarPosition, WordListDifficulty};
+use crate::error::{self, TypingTestError};
+use crate::ui;
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode},
-    style::{Print, Stylize},
+    style::{Print, ResetColor, Stylize},
     terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
@@ -14,31 +16,128 @@ struct MenuState {
     status_message: String,
 }
 
-const MENU_ITEMS: [&str; 5] = [
-    "Game Mode",
-    "Test Length (Words)",
-    "Time Limit (Seconds)",
-    "Layout Theme",
-    "Language",
+impl MenuState {
+    fn selected(&self) -> MenuItem {
+        MENU_ITEMS[self.selected_item]
+    }
+}
+
+/// A line of the settings menu; `MENU_ITEMS` sets the order they are listed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuItem {
+    GameMode,
+    TestLength,
+    TimeLimit,
+    LayoutTheme,
+    Language,
+    CaseInsensitive,
+    GraphHeight,
+    RandomLayout,
+    EnterAdvances,
+    ShowKpm,
+    CaretBlink,
+    CorrectionsPerWord,
+    FrequencyWeighted,
+    TargetWpm,
+    FocusMode,
+    AutoNextTest,
+    GhostPace,
+    AutoAdvance,
+    Sounds,
+    StatsBar,
+    Difficulty,
+}
+
+impl MenuItem {
+    fn label(self) -> &'static str {
+        match self {
+            MenuItem::GameMode => "Game Mode",
+            MenuItem::TestLength => "Test Length (Words)",
+            MenuItem::TimeLimit => "Time Limit (Seconds)",
+            MenuItem::LayoutTheme => "Layout Theme",
+            MenuItem::Language => "Language",
+            MenuItem::CaseInsensitive => "Case Insensitive",
+            MenuItem::GraphHeight => "Graph Height",
+            MenuItem::RandomLayout => "Random Layout",
+            MenuItem::EnterAdvances => "Enter Advances Word",
+            MenuItem::ShowKpm => "Show KPM",
+            MenuItem::CaretBlink => "Caret Blink",
+            MenuItem::CorrectionsPerWord => "Corrections Per Word",
+            MenuItem::FrequencyWeighted => "Frequency Weighted",
+            MenuItem::TargetWpm => "Target WPM",
+            MenuItem::FocusMode => "Focus Mode",
+            MenuItem::AutoNextTest => "Auto Next Test",
+            MenuItem::GhostPace => "Ghost Pace",
+            MenuItem::AutoAdvance => "Auto Advance",
+            MenuItem::Sounds => "Sounds",
+            MenuItem::StatsBar => "Stats Bar",
+            MenuItem::Difficulty => "Difficulty",
+        }
+    }
+}
+
+const MENU_ITEMS: [MenuItem; 21] = [
+    MenuItem::GameMode,
+    MenuItem::TestLength,
+    MenuItem::TimeLimit,
+    MenuItem::LayoutTheme,
+    MenuItem::Language,
+    MenuItem::CaseInsensitive,
+    MenuItem::GraphHeight,
+    MenuItem::RandomLayout,
+    MenuItem::EnterAdvances,
+    MenuItem::ShowKpm,
+    MenuItem::CaretBlink,
+    MenuItem::CorrectionsPerWord,
+    MenuItem::FrequencyWeighted,
+    MenuItem::TargetWpm,
+    MenuItem::FocusMode,
+    MenuItem::AutoNextTest,
+    MenuItem::GhostPace,
+    MenuItem::AutoAdvance,
+    MenuItem::Sounds,
+    MenuItem::StatsBar,
+    MenuItem::Difficulty,
 ];
 
-pub fn run() -> io::Result<()> {
+// Bounds for a time limit typed in directly.
+const MIN_TIME_LIMIT: u64 = 10;
+const MAX_TIME_LIMIT: u64 = 3600;
+// Words of the highlighted pack shown on the language screen.
+const PREVIEW_WORDS: usize = 12;
+// Items listed above the selected one once the menu is too tall to fit and scrolls.
+const MAX_ITEMS_ABOVE_SELECTED: usize = 3;
+// Row of the first item, and rows each item takes.
+const FIRST_ITEM_Y: u16 = 5;
+const ITEM_SPACING: u16 = 2;
+
+pub fn run() -> error::Result<()> {
+    open("")
+}
+
+/// The menu as shown to a new user before their first test.
+pub fn run_first_time() -> error::Result<()> {
+    open("Welcome! Pick your settings, press 'enter' to save them, then 'q' to start typing.")
+}
+
+fn open(status_message: &str) -> error::Result<()> {
     let mut stdout = io::stdout();
-    stdout.execute(EnterAlternateScreen)?;
-    terminal::enable_raw_mode()?;
+    stdout.execute(EnterAlternateScreen).map_err(TypingTestError::Terminal)?;
+    terminal::enable_raw_mode().map_err(TypingTestError::Terminal)?;
 
-    let result = show_menu(&mut stdout);
+    let result = show_menu(&mut stdout, status_message);
 
-    terminal::disable_raw_mode()?;
-    stdout.execute(LeaveAlternateScreen)?;
+    stdout.execute(ResetColor)?;
+    terminal::disable_raw_mode().map_err(TypingTestError::Terminal)?;
+    stdout.execute(LeaveAlternateScreen).map_err(TypingTestError::Terminal)?;
     result
 }
 
-pub fn show_menu(stdout: &mut io::Stdout) -> io::Result<()> {
+pub fn show_menu(stdout: &mut io::Stdout, status_message: &str) -> error::Result<()> {
     let mut state = MenuState {
-        config: config::load_config(),
+        config: config::load_config(true),
         selected_item: 0,
-        status_message: "".to_string(),
+        status_message: status_message.to_string(),
     };
 
     loop {
@@ -55,6 +154,20 @@ pub fn show_menu(stdout: &mut io::Stdout) -> io::Result<()> {
                 }
                 KeyCode::Left => change_value(&mut state, -1),
                 KeyCode::Right => change_value(&mut state, 1),
+                KeyCode::Enter if state.selected() == MenuItem::TimeLimit => {
+                    let prompt = format!("Time limit in seconds ({}-{}): ", MIN_TIME_LIMIT, MAX_TIME_LIMIT);
+                    match read_number(stdout, &state, &prompt, MIN_TIME_LIMIT, MAX_TIME_LIMIT)? {
+                        Some(secs) => {
+                            state.config.default_time_limit = secs;
+                            state.status_message = match config::save_config(&state.config) {
+                                Ok(_) => format!("Time limit set to {} seconds and saved.", secs),
+                                Err(e) => format!("Error saving config: {}", e),
+                            };
+                        }
+                        None => state.status_message.clear(),
+                    }
+                }
+                KeyCode::Enter if state.selected() == MenuItem::Language => manage_languages(stdout, &mut state)?,
                 KeyCode::Enter => {
                     match config::save_config(&state.config) {
                         Ok(_) => state.status_message = "Config saved successfully!".to_string(),
@@ -69,82 +182,361 @@ pub fn show_menu(stdout: &mut io::Stdout) -> io::Result<()> {
 }
 
 fn change_value(state: &mut MenuState, direction: i32) {
-    match state.selected_item {
-        0 => { // Game Mode
+    match state.selected() {
+        MenuItem::GameMode => {
             state.config.game_mode = match state.config.game_mode {
                 GameMode::Words => GameMode::Time,
                 GameMode::Time => GameMode::Words,
             };
         }
-        1 => { // Test Length
+        MenuItem::TestLength => {
             let current = state.config.default_test_length as i32;
             state.config.default_test_length = (current + direction * 5).max(5) as usize;
         }
-        2 => { // Time Limit
-            let current = state.config.default_time_limit as i32;
-            state.config.default_time_limit = (current + direction * 5).max(10) as u64;
+        MenuItem::TimeLimit => {
+            // Stepping below the minimum reaches "no limit", and back up from there.
+            let current = state.config.default_time_limit;
+            state.config.default_time_limit = match (current, direction) {
+                (config::NO_TIME_LIMIT, d) if d > 0 => MIN_TIME_LIMIT,
+                (config::NO_TIME_LIMIT, _) => config::NO_TIME_LIMIT,
+                (MIN_TIME_LIMIT, d) if d < 0 => config::NO_TIME_LIMIT,
+                _ => (current as i32 + direction * 5).max(MIN_TIME_LIMIT as i32) as u64,
+            };
         }
-        3 => { // Layout Theme
+        MenuItem::LayoutTheme => {
             state.config.layout_theme = match state.config.layout_theme {
                 LayoutTheme::Default => LayoutTheme::Boxes,
                 LayoutTheme::Boxes => LayoutTheme::Default,
             };
         }
-        4 => { // Language
-            let current_language_index = state.config.language_packs.iter().position(|p| p.name == state.config.selected_language).unwrap_or(0);
-            let next_index = (current_language_index as i32 + direction).rem_euclid(state.config.language_packs.len() as i32) as usize;
-            state.config.selected_language = state.config.language_packs[next_index].name.clone();
+        MenuItem::Language => {
+            // The entry after the last pack is the mixed list of all packs.
+            let packs = &state.config.language_packs;
+            let current_language_index = if state.config.selected_language == config::MIXED_LANGUAGE {
+                packs.len()
+            } else {
+                packs.iter().position(|p| p.name == state.config.selected_language).unwrap_or(0)
+            };
+            let next_index = (current_language_index as i32 + direction).rem_euclid(packs.len() as i32 + 1) as usize;
+            state.config.selected_language = match packs.get(next_index) {
+                Some(pack) => pack.name.clone(),
+                None => config::MIXED_LANGUAGE.to_string(),
+            };
+        }
+        MenuItem::CaseInsensitive => {
+            state.config.case_insensitive = !state.config.case_insensitive;
+        }
+        MenuItem::GraphHeight => {
+            let current = state.config.graph_height as i32;
+            state.config.graph_height = (current + direction).clamp(5, 40) as u16;
+        }
+        MenuItem::RandomLayout => {
+            state.config.random_layout = !state.config.random_layout;
+        }
+        MenuItem::EnterAdvances => {
+            state.config.enter_advances = !state.config.enter_advances;
+        }
+        MenuItem::ShowKpm => {
+            state.config.show_kpm = !state.config.show_kpm;
+        }
+        MenuItem::CaretBlink => {
+            state.config.caret_blink = !state.config.caret_blink;
+        }
+        MenuItem::CorrectionsPerWord => {
+            // Stepping down from 0 turns the limit off
+            let current = state.config.max_corrections_per_word.map_or(-1, |n| n as i32);
+            let next = (current + direction).clamp(-1, 20);
+            state.config.max_corrections_per_word = if next < 0 { None } else { Some(next as u32) };
+        }
+        MenuItem::FrequencyWeighted => {
+            state.config.frequency_weighted = !state.config.frequency_weighted;
+        }
+        MenuItem::TargetWpm => {
+            // Stepping below 10 turns the goal off
+            let current = state.config.target_wpm.map_or(5, |wpm| wpm as i32);
+            let next = (current + direction * 5).clamp(5, 300);
+            state.config.target_wpm = if next < 10 { None } else { Some(next as f64) };
+        }
+        MenuItem::FocusMode => {
+            state.config.focus_mode = !state.config.focus_mode;
+        }
+        MenuItem::AutoNextTest => {
+            // Stepping down from 1 turns it off
+            let current = state.config.auto_next_secs.map_or(0, |secs| secs as i32);
+            let next = (current + direction).clamp(0, 30);
+            state.config.auto_next_secs = if next == 0 { None } else { Some(next as u64) };
+        }
+        MenuItem::GhostPace => {
+            state.config.ghost_pace = !state.config.ghost_pace;
+        }
+        MenuItem::AutoAdvance => {
+            state.config.auto_advance_on_correct = !state.config.auto_advance_on_correct;
+        }
+        MenuItem::Sounds => {
+            // A shortcut for the individual sound settings: Off -> Errors -> All
+            let (beep, chime) = match (state.config.beep_on_error, state.config.chime_on_complete) {
+                (false, false) => (true, false),
+                (true, false) => (true, true),
+                _ => (false, false),
+            };
+            state.config.beep_on_error = beep;
+            state.config.chime_on_complete = chime;
+        }
+        MenuItem::StatsBar => {
+            state.config.stats_bar_position = match state.config.stats_bar_position {
+                StatsBarPosition::Top => StatsBarPosition::Bottom,
+                StatsBarPosition::Bottom => StatsBarPosition::Top,
+            };
+        }
+        MenuItem::Difficulty => {
+            let levels = [WordListDifficulty::Easy, WordListDifficulty::Medium, WordListDifficulty::Hard];
+            let current = levels.iter().position(|d| *d == state.config.word_list_difficulty).unwrap_or(1) as i32;
+            state.config.word_list_difficulty = levels[(current + direction).clamp(0, 2) as usize];
         }
-        _ => {},
     }
 }
 
 fn draw_menu(stdout: &mut Stdout, state: &MenuState) -> io::Result<()> {
     let (width, height) = terminal::size()?;
+    ui::apply_ui_colors(stdout, &state.config)?;
     stdout.execute(Clear(ClearType::All))?;
 
     let title = "Settings Menu";
-    let title_x = (width - title.len() as u16) / 2;
+    let title_x = ui::centered_x(width, title);
     stdout
         .execute(cursor::MoveTo(title_x, 2))?
         .execute(Print(title.bold()))?;
+    ui::apply_ui_colors(stdout, &state.config)?;
 
-    for (i, item) in MENU_ITEMS.iter().enumerate() {
-        let y = 5 + i as u16 * 2;
-        let value_str = get_value_string(&state.config, i);
+    // Items stop above the status line; scroll the list so the selected one stays in view.
+    let rows = height.saturating_sub(4).saturating_sub(FIRST_ITEM_Y);
+    let visible = (rows.div_ceil(ITEM_SPACING) as usize).max(1);
+    let first_visible = state
+        .selected_item
+        .saturating_sub(MAX_ITEMS_ABOVE_SELECTED)
+        .min(MENU_ITEMS.len().saturating_sub(visible))
+        .max((state.selected_item + 1).saturating_sub(visible));
+    for (i, item) in MENU_ITEMS.iter().enumerate().skip(first_visible).take(visible) {
+        let y = FIRST_ITEM_Y + (i - first_visible) as u16 * ITEM_SPACING;
+        let value_str = get_value_string(&state.config, *item);
 
-        let line = format!("{: <25}: {}", item, value_str);
+        let line = format!("{: <25}: {}", item.label(), value_str);
         
         if i == state.selected_item {
             stdout
                 .execute(cursor::MoveTo(5, y))?
                 .execute(Print(line.negative()))?;
+            ui::apply_ui_colors(stdout, &state.config)?;
         } else {
             stdout.execute(cursor::MoveTo(5, y))?.execute(Print(line))?;
         }
     }
 
-    let instructions = "Use ↑/↓ to navigate, ←/→ to change values, 'enter' to save, 'q' to quit.";
-    let status_x = (width - state.status_message.len() as u16) / 2;
-    let inst_x = (width - instructions.len() as u16) / 2;
+    let instructions = "Use ↑/↓ to navigate, ←/→ to change values, 'enter' to save (or type a time limit, or manage languages), 'q' to quit.";
+    let status_x = ui::centered_x(width, &state.status_message);
+    let inst_x = ui::centered_x(width, instructions);
 
     stdout
-        .execute(cursor::MoveTo(status_x, height - 4))?
+        .execute(cursor::MoveTo(status_x, height.saturating_sub(4)))?
         .execute(Print(&state.status_message))?;
     stdout
-        .execute(cursor::MoveTo(inst_x, height - 2))?
+        .execute(cursor::MoveTo(inst_x, height.saturating_sub(2)))?
         .execute(Print(instructions.dark_grey()))?;
 
     stdout.flush()
 }
 
-fn get_value_string(config: &Config, item_index: usize) -> String {
-    match item_index {
-        0 => format!("{:?}", config.game_mode),
-        1 => format!("{} words", config.default_test_length),
-        2 => format!("{} seconds", config.default_time_limit),
-        3 => format!("{:?}", config.layout_theme),
-        4 => config.selected_language.clone(),
-        _ => "".to_string(),
+/// The language screen opened with Enter on the Language item: lists the installed
+/// packs with their word counts and a preview of the highlighted one, and lets the user
+/// pick the active pack or delete one after confirming.
+fn manage_languages(stdout: &mut Stdout, state: &mut MenuState) -> io::Result<()> {
+    let mut selected = state
+        .config
+        .language_packs
+        .iter()
+        .position(|p| p.name == state.config.selected_language)
+        .unwrap_or(0);
+    let mut confirm_delete = false;
+    let mut message = String::new();
+
+    loop {
+        let packs = &state.config.language_packs;
+        selected = selected.min(packs.len().saturating_sub(1));
+        let prompt = match packs.get(selected) {
+            Some(pack) if confirm_delete => format!("Delete '{}' and its file? (y/n)", pack.name),
+            _ => message.clone(),
+        };
+        draw_languages(stdout, state, selected, &prompt)?;
+
+        let Event::Key(key_event) = event::read()? else {
+            continue;
+        };
+        if confirm_delete {
+            confirm_delete = false;
+            if key_event.code == KeyCode::Char('y') {
+                let name = state.config.language_packs[selected].name.clone();
+                message = match config::delete_language_pack(&mut state.config, &name) {
+                    Ok(()) => match config::save_config(&state.config) {
+                        Ok(_) => format!("Deleted '{}'.", name),
+                        Err(e) => format!("Deleted '{}', but saving the config failed: {}", name, e),
+                    },
+                    Err(e) => format!("Could not delete '{}': {}", name, e),
+                };
+            } else {
+                message.clear();
+            }
+            continue;
+        }
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Enter if !packs.is_empty() => {
+                let name = packs[selected].name.clone();
+                state.config.selected_language = name.clone();
+                message = match config::save_config(&state.config) {
+                    Ok(_) => format!("'{}' is now the active language.", name),
+                    Err(e) => format!("Error saving config: {}", e),
+                };
+            }
+            KeyCode::Char('d') if !packs.is_empty() => confirm_delete = true,
+            _ => {}
+        }
+    }
+    state.status_message.clear();
+    Ok(())
+}
+
+fn draw_languages(stdout: &mut Stdout, state: &MenuState, selected: usize, message: &str) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    ui::apply_ui_colors(stdout, &state.config)?;
+    stdout.execute(Clear(ClearType::All))?;
+
+    let title = "Language Packs";
+    stdout
+        .execute(cursor::MoveTo(ui::centered_x(width, title), 2))?
+        .execute(Print(title.bold()))?;
+    ui::apply_ui_colors(stdout, &state.config)?;
+
+    let packs = &state.config.language_packs;
+    if packs.is_empty() {
+        stdout
+            .execute(cursor::MoveTo(5, 5))?
+            .execute(Print("No language packs installed in the languages directory."))?;
+    }
+    for (i, pack) in packs.iter().enumerate() {
+        let y = 5 + i as u16;
+        if y + 6 > height {
+            break;
+        }
+        let active = if pack.name == state.config.selected_language { " (active)" } else { "" };
+        let line = format!("{: <25}{} words{}", pack.name, pack.words.len(), active);
+        stdout.execute(cursor::MoveTo(5, y))?;
+        if i == selected {
+            stdout.execute(Print(line.negative()))?;
+            ui::apply_ui_colors(stdout, &state.config)?;
+        } else {
+            stdout.execute(Print(line))?;
+        }
+    }
+
+    if let Some(pack) = packs.get(selected) {
+        let words: Vec<&str> = pack.words.iter().take(PREVIEW_WORDS).map(String::as_str).collect();
+        let preview = format!("Preview: {}", words.join(" "));
+        stdout
+            .execute(cursor::MoveTo(5, height.saturating_sub(6)))?
+            .execute(Print(preview.dark_grey()))?;
+        ui::apply_ui_colors(stdout, &state.config)?;
+    }
+
+    let instructions = "Use ↑/↓ to select, 'enter' to make active, 'd' to delete, 'q' to go back.";
+    stdout
+        .execute(cursor::MoveTo(ui::centered_x(width, message), height.saturating_sub(4)))?
+        .execute(Print(message))?;
+    stdout
+        .execute(cursor::MoveTo(ui::centered_x(width, instructions), height.saturating_sub(2)))?
+        .execute(Print(instructions.dark_grey()))?;
+    stdout.flush()
+}
+
+/// Prompts for a whole number between `min` and `max` on the status line, on top of the
+/// menu. Returns `None` if the user cancels with Esc.
+fn read_number(stdout: &mut Stdout, state: &MenuState, prompt: &str, min: u64, max: u64) -> io::Result<Option<u64>> {
+    let mut input = String::new();
+    let mut error = String::new();
+    loop {
+        draw_menu(stdout, state)?;
+        let (width, height) = terminal::size()?;
+        let line = format!("{}{}_", prompt, input);
+        stdout
+            .execute(cursor::MoveTo(0, height.saturating_sub(4)))?
+            .execute(Clear(ClearType::CurrentLine))?
+            .execute(cursor::MoveTo(ui::centered_x(width, &line), height.saturating_sub(4)))?
+            .execute(Print(&line))?;
+        if !error.is_empty() {
+            stdout
+                .execute(cursor::MoveTo(ui::centered_x(width, &error), height.saturating_sub(3)))?
+                .execute(Print(error.as_str().red()))?;
+            ui::apply_ui_colors(stdout, &state.config)?;
+        }
+        stdout.flush()?;
+
+        if let Event::Key(key_event) = event::read()? {
+            match key_event.code {
+                KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 9 => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => match input.parse::<u64>() {
+                    Ok(n) if (min..=max).contains(&n) => return Ok(Some(n)),
+                    _ => error = format!("Enter a number from {} to {}.", min, max),
+                },
+                KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn get_value_string(config: &Config, item: MenuItem) -> String {
+    match item {
+        MenuItem::GameMode => format!("{:?}", config.game_mode),
+        MenuItem::TestLength => format!("{} words", config.default_test_length),
+        MenuItem::TimeLimit if config.default_time_limit == config::NO_TIME_LIMIT => "No limit (until Esc)".to_string(),
+        MenuItem::TimeLimit => format!("{} seconds", config.default_time_limit),
+        MenuItem::LayoutTheme => format!("{:?}", config.layout_theme),
+        MenuItem::Language if config.selected_language == config::MIXED_LANGUAGE => "mixed (all languages)".to_string(),
+        MenuItem::Language => config.selected_language.clone(),
+        MenuItem::CaseInsensitive => if config.case_insensitive { "On" } else { "Off" }.to_string(),
+        MenuItem::GraphHeight => format!("{} rows", config.graph_height),
+        MenuItem::RandomLayout => if config.random_layout { "On" } else { "Off" }.to_string(),
+        MenuItem::EnterAdvances => if config.enter_advances { "On" } else { "Off" }.to_string(),
+        MenuItem::ShowKpm => if config.show_kpm { "On" } else { "Off" }.to_string(),
+        MenuItem::CaretBlink => if config.caret_blink { "On" } else { "Off" }.to_string(),
+        MenuItem::CorrectionsPerWord => match config.max_corrections_per_word {
+            Some(max) => format!("{} backspaces", max),
+            None => "Unlimited".to_string(),
+        },
+        MenuItem::FrequencyWeighted => if config.frequency_weighted { "On" } else { "Off" }.to_string(),
+        MenuItem::TargetWpm => match config.target_wpm {
+            Some(wpm) => format!("{:.0} WPM", wpm),
+            None => "Off".to_string(),
+        },
+        MenuItem::FocusMode => if config.focus_mode { "On" } else { "Off" }.to_string(),
+        MenuItem::AutoNextTest => match config.auto_next_secs {
+            Some(secs) => format!("after {} seconds", secs),
+            None => "Off".to_string(),
+        },
+        MenuItem::GhostPace => if config.ghost_pace { "On" } else { "Off" }.to_string(),
+        MenuItem::AutoAdvance => if config.auto_advance_on_correct { "On" } else { "Off" }.to_string(),
+        MenuItem::Sounds => match (config.beep_on_error, config.chime_on_complete) {
+            (false, false) => "Off",
+            (true, false) => "Errors",
+            (true, true) => "All",
+            // Only reachable by editing the config file.
+            (false, true) => "Completion",
+        }.to_string(),
+        MenuItem::StatsBar => format!("{:?}", config.stats_bar_position),
+        MenuItem::Difficulty => format!("{:?}", config.word_list_difficulty),
     }
 }
@@ -0,0 +1,66 @@
+use crate::graphemes;
+use crate::scoring;
+use std::collections::HashMap;
+
+// Fewer distinct weak words than this is not enough to build a useful drill.
+const MIN_WEAK_WORDS: usize = 5;
+// Only the most troublesome words are drilled.
+const WEAK_WORD_POOL: usize = 50;
+// A word is "slow" when its time per character exceeds the test average by this factor.
+const SLOW_FACTOR: f64 = 1.5;
+
+/// Builds a word list from the most troublesome words. Returns `None` when there
+/// isn't enough history yet.
+pub fn weak_word_list(weak_words: &HashMap<String, u32>) -> Option<Vec<String>> {
+    if weak_words.len() < MIN_WEAK_WORDS {
+        return None;
+    }
+
+    let mut ranked: Vec<(&String, &u32)> = weak_words.iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    Some(
+        ranked
+            .into_iter()
+            .take(WEAK_WORD_POOL)
+            .map(|(word, _)| word.clone())
+            .collect(),
+    )
+}
+
+/// Updates the weak-word tally from a finished test. Mistyped and unusually slow words
+/// gain a point; words typed cleanly at a normal pace lose one and drop out at zero.
+/// Only words with a recorded time (i.e. words that were finished) are considered.
+pub fn record_weak_words(
+    weak_words: &mut HashMap<String, u32>,
+    words: &[String],
+    typed: &[String],
+    word_times: &[Option<f64>],
+    case_insensitive: bool,
+    optional_chars: &[char],
+) {
+    let finished: Vec<(&String, &String, f64)> = words
+        .iter()
+        .zip(typed.iter())
+        .zip(word_times.iter())
+        .filter_map(|((word, typed), time)| time.map(|t| (word, typed, t)))
+        .collect();
+
+    let per_char = |word: &String, time: f64| time / graphemes::count(word).max(1) as f64;
+    let average = if finished.is_empty() {
+        0.0
+    } else {
+        finished.iter().map(|(w, _, t)| per_char(w, *t)).sum::<f64>() / finished.len() as f64
+    };
+
+    for (word, typed, time) in finished {
+        let slow = average > 0.0 && per_char(word, time) > average * SLOW_FACTOR;
+        if !scoring::words_match(typed, word, case_insensitive, optional_chars) || slow {
+            *weak_words.entry(word.clone()).or_default() += 1;
+        } else if let Some(count) = weak_words.get_mut(word) {
+            *count -= 1;
+            if *count == 0 {
+                weak_words.remove(word);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config;
+use chrono::Local;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// The numbers behind one finished test, as shown on the results screen.
+pub struct TestReport {
+    pub mode: String,
+    pub wpm: f64,
+    pub raw_wpm: f64,
+    pub accuracy: f64,
+    pub consistency: Option<f64>,
+    pub duration: f64,
+    /// Mistyped words, already formatted with `ui::format_diff`.
+    pub mistakes: Vec<String>,
+}
+
+impl TestReport {
+    fn to_text(&self, timestamp: &str) -> String {
+        let mut lines = vec![
+            format!("Typing test report - {}", timestamp),
+            format!("Mode: {}", self.mode),
+            String::new(),
+            format!("WPM: {:.2}", self.wpm),
+            format!("Raw WPM: {:.2}", self.raw_wpm),
+            format!("Accuracy: {:.2}%", self.accuracy),
+            match self.consistency {
+                Some(consistency) => format!("Consistency: {:.0}%", consistency),
+                None => "Consistency: n/a".to_string(),
+            },
+            format!("Duration: {:.1}s", self.duration),
+            String::new(),
+        ];
+        if self.mistakes.is_empty() {
+            lines.push("No mistyped words.".to_string());
+        } else {
+            lines.push(format!("Mistyped words ({}):", self.mistakes.len()));
+            lines.extend(self.mistakes.iter().map(|m| format!("  {}", m)));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Writes the report to a timestamped text file in the config directory and
+    /// returns its path.
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let dir = config::get_config_dir().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Could not find config directory")
+        })?;
+        let now = Local::now();
+        let path = dir.join(format!("report_{}.txt", now.format("%Y%m%d_%H%M%S")));
+        fs::write(&path, self.to_text(&now.format("%Y-%m-%d %H:%M:%S").to_string()))?;
+        Ok(path)
+    }
+}
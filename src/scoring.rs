@@ -0,0 +1,551 @@
+use crate::config::AccuracyMode;
+use crate::graphemes::graphemes;
+
+/// Compares a typed character (grapheme cluster) against the target, optionally
+/// ignoring case.
+pub fn chars_match(typed: &str, target: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        typed.to_lowercase() == target.to_lowercase()
+    } else {
+        typed == target
+    }
+}
+
+/// Lines up the target's characters with what was typed when some characters are
+/// optional. Each entry is the index into `typed` that the target character is
+/// compared with (past the end for characters not typed yet), or `None` for an
+/// optional character that was left out.
+pub fn optional_alignment(
+    typed: &[&str],
+    target: &[&str],
+    case_insensitive: bool,
+    optional_chars: &[char],
+) -> Vec<Option<usize>> {
+    let is_optional = |g: &str| {
+        let mut chars = g.chars();
+        matches!((chars.next(), chars.next()), (Some(c), None) if optional_chars.contains(&c))
+    };
+
+    let mut next_typed = 0;
+    let mut alignment = Vec::with_capacity(target.len());
+    for (i, t) in target.iter().enumerate() {
+        if is_optional(t) {
+            let skipped = match typed.get(next_typed) {
+                Some(g) => !chars_match(g, t, case_insensitive),
+                // Trailing optional characters count as left out once the rest is typed.
+                None => !typed.is_empty() && target[i..].iter().all(|g| is_optional(g)),
+            };
+            if skipped {
+                alignment.push(None);
+                continue;
+            }
+        }
+        alignment.push(Some(next_typed));
+        next_typed += 1;
+    }
+    alignment
+}
+
+/// The target as it should be typed given `typed`: `target` without the optional
+/// characters that were left out.
+pub fn resolve_optional(typed: &str, target: &str, case_insensitive: bool, optional_chars: &[char]) -> String {
+    if optional_chars.is_empty() {
+        return target.to_string();
+    }
+    let target_graphemes = graphemes(target);
+    optional_alignment(&graphemes(typed), &target_graphemes, case_insensitive, optional_chars)
+        .iter()
+        .zip(target_graphemes)
+        .filter(|(aligned, _)| aligned.is_some())
+        .map(|(_, g)| g)
+        .collect()
+}
+
+/// Whether a typed word matches its target exactly (up to case, if enabled, and
+/// ignoring left-out optional characters).
+pub fn words_match(typed: &str, target: &str, case_insensitive: bool, optional_chars: &[char]) -> bool {
+    let target = resolve_optional(typed, target, case_insensitive, optional_chars);
+    let (typed, target) = (graphemes(typed), graphemes(&target));
+    typed.len() == target.len()
+        && typed
+            .iter()
+            .zip(target.iter())
+            .all(|(a, b)| chars_match(a, b, case_insensitive))
+}
+
+/// Counts correctly typed characters across all words, used for the live WPM.
+pub fn correct_chars(
+    typed_words: &[String],
+    words: &[String],
+    case_insensitive: bool,
+    optional_chars: &[char],
+) -> usize {
+    typed_words
+        .iter()
+        .zip(words.iter())
+        .map(|(typed, original)| {
+            let original = resolve_optional(typed, original, case_insensitive, optional_chars);
+            graphemes(typed)
+                .into_iter()
+                .zip(graphemes(&original))
+                .filter(|(a, b)| chars_match(a, b, case_insensitive))
+                .count()
+        })
+        .sum()
+}
+
+/// Finds the `(word, character)` reached after typing `chars` characters of `words`,
+/// counting the space after each word. Landing on a space gives the start of the next
+/// word; `None` once past the end.
+pub fn position_at(words: &[String], chars: usize) -> Option<(usize, usize)> {
+    let mut remaining = chars;
+    for (i, word) in words.iter().enumerate() {
+        let len = crate::graphemes::count(word);
+        if remaining < len {
+            return Some((i, remaining));
+        }
+        remaining = remaining.saturating_sub(len + 1);
+        if remaining == 0 && i + 1 < words.len() {
+            return Some((i + 1, 0));
+        }
+    }
+    None
+}
+
+/// Words per minute for `correct_chars` typed in `secs`, counting five characters as
+/// a word. 0 when no time has passed.
+pub fn wpm(correct_chars: usize, secs: f64) -> f64 {
+    if secs > 0.0 {
+        (correct_chars as f64 / 5.0) / (secs / 60.0)
+    } else {
+        0.0
+    }
+}
+
+/// The time a test took, given the time measured from the first keystroke. The clock
+/// starts on that keystroke, so it took no time at all; `grace_ms` adds the time it
+/// would have taken, which matters on short tests.
+pub fn with_first_key_grace(measured_secs: f64, grace_ms: u64) -> f64 {
+    measured_secs + grace_ms as f64 / 1000.0
+}
+
+/// How many words the final score covers when the test ends on word `current`: every
+/// finished word plus the one in progress, as far as it was typed. A word not started
+/// yet never counts.
+pub fn scored_words(typed_words: &[String], current: usize) -> usize {
+    current + usize::from(typed_words.get(current).is_some_and(|typed| !typed.is_empty()))
+}
+
+/// Counts every keystroke that produced a character over the first `word_count` words,
+/// right or wrong, including the spaces between them.
+pub fn keystrokes(typed_words: &[String], word_count: usize) -> usize {
+    let chars: usize = typed_words
+        .iter()
+        .take(word_count)
+        .map(|typed| typed.chars().count())
+        .sum();
+    chars + word_count.saturating_sub(1)
+}
+
+/// Splits a timed test into `segment_secs`-long segments and returns the WPM of each.
+/// `timeline[i]` holds the correct characters typed by the end of second `i + 1`;
+/// seconds past the end of the timeline use `final_correct`.
+pub fn segment_wpms(timeline: &[usize], final_correct: usize, segment_secs: usize, total_secs: usize) -> Vec<f64> {
+    let correct_at = |second: usize| {
+        if second == 0 {
+            0
+        } else {
+            timeline.get(second - 1).copied().unwrap_or(final_correct)
+        }
+    };
+
+    (0..total_secs)
+        .step_by(segment_secs.max(1))
+        .map(|start| {
+            let end = (start + segment_secs).min(total_secs);
+            let chars = correct_at(end).saturating_sub(correct_at(start));
+            (chars as f64 / 5.0) / ((end - start) as f64 / 60.0)
+        })
+        .collect()
+}
+
+/// Counts correct and incorrect characters over the first `word_count` words for the
+/// final score. Characters typed past the end of a word count as incorrect.
+pub fn char_totals(
+    typed_words: &[String],
+    words: &[String],
+    word_count: usize,
+    case_insensitive: bool,
+    optional_chars: &[char],
+) -> (usize, usize) {
+    typed_words
+        .iter()
+        .zip(words.iter())
+        .take(word_count)
+        .fold((0, 0), |(c, i), (typed, original)| {
+            let (wc, wi) = word_totals(typed, original, case_insensitive, optional_chars);
+            (c + wc, i + wi)
+        })
+}
+
+/// `char_totals` for a single word.
+pub fn word_totals(typed: &str, original: &str, case_insensitive: bool, optional_chars: &[char]) -> (usize, usize) {
+    let original = resolve_optional(typed, original, case_insensitive, optional_chars);
+    let (typed, original) = (graphemes(typed), graphemes(&original));
+    let correct = typed
+        .iter()
+        .zip(original.iter())
+        .filter(|(tc, oc)| chars_match(tc, oc, case_insensitive))
+        .count();
+    (correct, typed.len() - correct)
+}
+
+/// Accuracy in percent over the first `word_count` words. `Character` pools every typed
+/// character; `WordAverage` averages each word's own accuracy, so one badly mangled long
+/// word weighs no more than a short one. Words with nothing typed are left out, and
+/// 100 is returned when nothing was typed at all.
+pub fn accuracy(
+    mode: AccuracyMode,
+    typed_words: &[String],
+    words: &[String],
+    word_count: usize,
+    case_insensitive: bool,
+    optional_chars: &[char],
+) -> f64 {
+    let totals: Vec<(usize, usize)> = typed_words
+        .iter()
+        .zip(words.iter())
+        .take(word_count)
+        .map(|(typed, original)| word_totals(typed, original, case_insensitive, optional_chars))
+        .filter(|(c, i)| c + i > 0)
+        .collect();
+    if totals.is_empty() {
+        return 100.0;
+    }
+    match mode {
+        AccuracyMode::Character => {
+            let (c, i) = totals.iter().fold((0, 0), |(c, i), (wc, wi)| (c + wc, i + wi));
+            c as f64 / (c + i) as f64 * 100.0
+        }
+        AccuracyMode::WordAverage => {
+            let sum: f64 = totals.iter().map(|(c, i)| *c as f64 / (c + i) as f64).sum();
+            sum / totals.len() as f64 * 100.0
+        }
+    }
+}
+
+/// One step of aligning a typed word against its target, by grapheme cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp<'a> {
+    Match(&'a str),
+    /// `typed` was entered where `target` belonged.
+    Substitute { typed: &'a str, target: &'a str },
+    /// An extra character that isn't in the target.
+    Insert(&'a str),
+    /// A target character that was skipped.
+    Delete(&'a str),
+}
+
+/// Aligns `typed` against `target` with the fewest edits (Levenshtein distance), so a
+/// dropped or doubled letter shows up as one mistake instead of shifting the rest.
+pub fn align_word<'a>(typed: &'a str, target: &'a str, case_insensitive: bool) -> Vec<DiffOp<'a>> {
+    let typed = graphemes(typed);
+    let target = graphemes(target);
+    let (n, m) = (typed.len(), target.len());
+
+    // cost[i][j]: edits needed to turn typed[..i] into target[..j].
+    let mut cost = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in cost[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let sub = if chars_match(typed[i - 1], target[j - 1], case_insensitive) { 0 } else { 1 };
+            cost[i][j] = (cost[i - 1][j - 1] + sub)
+                .min(cost[i - 1][j] + 1)
+                .min(cost[i][j - 1] + 1);
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let same = chars_match(typed[i - 1], target[j - 1], case_insensitive);
+            if cost[i][j] == cost[i - 1][j - 1] + if same { 0 } else { 1 } {
+                ops.push(if same {
+                    DiffOp::Match(target[j - 1])
+                } else {
+                    DiffOp::Substitute { typed: typed[i - 1], target: target[j - 1] }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            ops.push(DiffOp::Insert(typed[i - 1]));
+            i -= 1;
+        } else {
+            ops.push(DiffOp::Delete(target[j - 1]));
+            j -= 1;
+        }
+    }
+    ops.reverse();
+    ops
+}
+
+/// The WPM each word was typed at, or `None` for words without a usable time.
+pub fn word_wpms(words: &[String], word_times: &[Option<f64>]) -> Vec<Option<f64>> {
+    words
+        .iter()
+        .zip(word_times.iter())
+        .map(|(word, time)| {
+            let secs = (*time)?;
+            // Count the following space, as WPM does.
+            (secs > 0.0).then(|| ((crate::graphemes::count(word) + 1) as f64 / 5.0) / (secs / 60.0))
+        })
+        .collect()
+}
+
+/// How steady the pace was across words, from 0 (erratic) to 100 (every word at the
+/// same speed): one minus the coefficient of variation of per-word WPM. `None` with
+/// fewer than two timed words.
+pub fn consistency(words: &[String], word_times: &[Option<f64>]) -> Option<f64> {
+    let word_wpms: Vec<f64> = word_wpms(words, word_times).into_iter().flatten().collect();
+    if word_wpms.len() < 2 {
+        return None;
+    }
+
+    let mean = word_wpms.iter().sum::<f64>() / word_wpms.len() as f64;
+    let variance = word_wpms.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / word_wpms.len() as f64;
+    Some(((1.0 - variance.sqrt() / mean) * 100.0).clamp(0.0, 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chars_match_folds_case_only_when_enabled() {
+        assert!(chars_match("A", "a", true));
+        assert!(chars_match("a", "a", true));
+        assert!(!chars_match("b", "a", true));
+        assert!(!chars_match("A", "a", false));
+        assert!(chars_match("a", "a", false));
+    }
+
+    #[test]
+    fn words_match_mixed_case_against_lowercase_target() {
+        assert!(words_match("HeLLo", "hello", true, &[]));
+        assert!(!words_match("HeLLo", "hello", false, &[]));
+        assert!(words_match("hello", "hello", false, &[]));
+        assert!(!words_match("Help", "hello", true, &[]));
+        assert!(!words_match("HELL", "hello", true, &[]));
+    }
+
+    #[test]
+    fn live_and_final_counts_follow_case_insensitive() {
+        let typed = vec!["ThE".to_string(), "Cat".to_string()];
+        let words = vec!["the".to_string(), "cat".to_string()];
+        assert_eq!(correct_chars(&typed, &words, true, &[]), 6);
+        assert_eq!(correct_chars(&typed, &words, false, &[]), 3);
+        assert_eq!(char_totals(&typed, &words, 2, true, &[]), (6, 0));
+        assert_eq!(char_totals(&typed, &words, 2, false, &[]), (3, 3));
+    }
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn position_at_counts_the_space_after_each_word() {
+        let words = words(&["the", "cat", "sat"]);
+        assert_eq!(position_at(&words, 0), Some((0, 0)));
+        assert_eq!(position_at(&words, 2), Some((0, 2)));
+        // Landing on the space after "the" already gives the start of "cat".
+        assert_eq!(position_at(&words, 3), Some((1, 0)));
+        assert_eq!(position_at(&words, 4), Some((1, 0)));
+        assert_eq!(position_at(&words, 5), Some((1, 1)));
+        assert_eq!(position_at(&words, 10), Some((2, 2)));
+        assert_eq!(position_at(&words, 11), None);
+    }
+
+    #[test]
+    fn time_running_out_mid_word_scores_only_what_was_typed() {
+        let target = words(&["the", "quick", "brown"]);
+
+        // Time ran out two letters into "quick": the rest of it isn't a mistake.
+        let typed = words(&["the", "qu", ""]);
+        let scored = scored_words(&typed, 1);
+        assert_eq!(scored, 2);
+        assert_eq!(char_totals(&typed, &target, scored, false, &[]), (5, 0));
+        assert_eq!(accuracy(AccuracyMode::Character, &typed, &target, scored, false, &[]), 100.0);
+        // Over a 15 second test that's 5 correct characters, one word, in a quarter minute.
+        assert_eq!(wpm(5, 15.0), 4.0);
+
+        // A wrong letter in the partial word counts against accuracy.
+        let typed = words(&["the", "qx", ""]);
+        assert_eq!(char_totals(&typed, &target, scored, false, &[]), (4, 1));
+        assert_eq!(accuracy(AccuracyMode::Character, &typed, &target, scored, false, &[]), 80.0);
+    }
+
+    #[test]
+    fn time_running_out_after_a_space_leaves_out_the_next_word() {
+        let target = words(&["the", "quick", "brown"]);
+        let typed = words(&["the", "quick", ""]);
+        // The caret moved on to "brown" but nothing of it was typed.
+        let scored = scored_words(&typed, 2);
+        assert_eq!(scored, 2);
+        assert_eq!(char_totals(&typed, &target, scored, false, &[]), (8, 0));
+        assert_eq!(scored_words(&typed, 3), 3);
+    }
+
+    #[test]
+    fn first_key_grace_on_a_five_word_test() {
+        // The README example: a 5-word test of 30 correct characters typed in 5 seconds.
+        assert_eq!(wpm(30, with_first_key_grace(5.0, 0)), 72.0);
+        let with_grace = wpm(30, with_first_key_grace(5.0, 200));
+        assert!((with_grace - 69.23).abs() < 0.01, "{}", with_grace);
+    }
+
+    #[test]
+    fn accuracy_modes_differ_on_uneven_words() {
+        // One long word typed completely wrong, then three short ones typed right.
+        let target = words(&["extraordinary", "a", "is", "to"]);
+        let typed = words(&["qqqqqqqqqqqqq", "a", "is", "to"]);
+        let character = accuracy(AccuracyMode::Character, &typed, &target, 4, false, &[]);
+        let word_average = accuracy(AccuracyMode::WordAverage, &typed, &target, 4, false, &[]);
+        assert!((character - 5.0 / 18.0 * 100.0).abs() < 1e-9);
+        assert!((word_average - 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn accuracy_leaves_out_untyped_words() {
+        let target = words(&["cat", "dog"]);
+        let typed = words(&["cat", ""]);
+        assert_eq!(accuracy(AccuracyMode::Character, &typed, &target, 2, false, &[]), 100.0);
+        assert_eq!(accuracy(AccuracyMode::WordAverage, &typed, &target, 2, false, &[]), 100.0);
+        assert_eq!(accuracy(AccuracyMode::WordAverage, &[], &[], 0, false, &[]), 100.0);
+    }
+
+    #[test]
+    fn optional_apostrophe_may_be_left_out_or_typed() {
+        let optional = ['\''];
+        assert!(words_match("dont", "don't", false, &optional));
+        assert!(words_match("don't", "don't", false, &optional));
+        assert!(!words_match("dont", "don't", false, &[]));
+        assert_eq!(word_totals("dont", "don't", false, &optional), (4, 0));
+        assert_eq!(word_totals("don't", "don't", false, &optional), (5, 0));
+    }
+
+    #[test]
+    fn optional_alignment_skips_only_left_out_characters() {
+        let target = ["d", "o", "n", "'", "t"];
+        assert_eq!(
+            optional_alignment(&["d", "o", "n", "t"], &target, false, &['\'']),
+            vec![Some(0), Some(1), Some(2), None, Some(3)]
+        );
+        assert_eq!(
+            optional_alignment(&["d", "o", "n", "'"], &target, false, &['\'']),
+            vec![Some(0), Some(1), Some(2), Some(3), Some(4)]
+        );
+        // Halfway through the word, the apostrophe is still expected next.
+        assert_eq!(resolve_optional("don", "don't", false, &['\'']), "don't");
+        // A trailing optional character counts as left out once the rest is typed.
+        assert_eq!(resolve_optional("rock", "rock-", false, &['-']), "rock");
+    }
+
+    #[test]
+    fn wrong_letter_in_place_of_an_optional_one_is_a_mistake() {
+        assert!(!words_match("donxt", "don't", false, &['\'']));
+        assert_eq!(word_totals("donxt", "don't", false, &['\'']), (3, 2));
+    }
+
+    #[test]
+    fn align_word_identical_words_are_all_matches() {
+        assert_eq!(align_word("cat", "cat", false), vec![DiffOp::Match("c"), DiffOp::Match("a"), DiffOp::Match("t")]);
+    }
+
+    #[test]
+    fn align_word_substitution() {
+        assert_eq!(
+            align_word("cot", "cat", false),
+            vec![DiffOp::Match("c"), DiffOp::Substitute { typed: "o", target: "a" }, DiffOp::Match("t")]
+        );
+    }
+
+    #[test]
+    fn align_word_dropped_letter_does_not_shift_the_rest() {
+        assert_eq!(
+            align_word("hllo", "hello", false),
+            vec![
+                DiffOp::Match("h"),
+                DiffOp::Delete("e"),
+                DiffOp::Match("l"),
+                DiffOp::Match("l"),
+                DiffOp::Match("o"),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_word_extra_letter() {
+        assert_eq!(
+            align_word("cart", "cat", false),
+            vec![DiffOp::Match("c"), DiffOp::Match("a"), DiffOp::Insert("r"), DiffOp::Match("t")]
+        );
+    }
+
+    #[test]
+    fn align_word_follows_case_insensitive() {
+        assert_eq!(align_word("Cat", "cat", true)[0], DiffOp::Match("c"));
+        assert_eq!(align_word("Cat", "cat", false)[0], DiffOp::Substitute { typed: "C", target: "c" });
+    }
+
+    /// Cumulative correct characters for a test typed at `rates[i]` characters in
+    /// second `i + 1`.
+    fn timeline(rates: &[usize]) -> Vec<usize> {
+        rates
+            .iter()
+            .scan(0, |total, rate| {
+                *total += rate;
+                Some(*total)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn segment_wpms_splits_at_segment_boundaries() {
+        // 5 characters a second (60 WPM) for 15 seconds, then 10 a second (120 WPM).
+        let rates: Vec<usize> = [vec![5; 15], vec![10; 15]].concat();
+        let timeline = timeline(&rates);
+        assert_eq!(segment_wpms(&timeline, 225, 15, 30), vec![60.0, 120.0]);
+    }
+
+    #[test]
+    fn segment_wpms_last_segment_covers_only_the_remaining_seconds() {
+        let rates: Vec<usize> = [vec![5; 15], vec![10; 5]].concat();
+        let timeline = timeline(&rates);
+        // The second segment is 5 seconds long, not 15.
+        assert_eq!(segment_wpms(&timeline, 125, 15, 20), vec![60.0, 120.0]);
+    }
+
+    #[test]
+    fn segment_wpms_for_a_test_shorter_than_one_segment() {
+        let timeline = timeline(&[5; 10]);
+        assert_eq!(segment_wpms(&timeline, 50, 15, 10), vec![60.0]);
+    }
+
+    #[test]
+    fn segment_wpms_uses_the_final_count_past_the_timeline() {
+        // The timeline stops after 10 seconds; the rest of the test is filled in from
+        // the final count.
+        let timeline = timeline(&[5; 10]);
+        assert_eq!(segment_wpms(&timeline, 75, 15, 15), vec![60.0]);
+        assert_eq!(segment_wpms(&timeline, 50, 5, 15), vec![60.0, 60.0, 0.0]);
+    }
+}
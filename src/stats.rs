@@ -1,106 +1,535 @@
-use crate::config::{self, Config, TestResult};
+use crate::clipboard;
+use crate::config::{self, Config, ModeInfo, ModeSort, StatsView as ViewMode, TestResult};
+use crate::error::{self, TypingTestError};
+use crate::ui;
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode},
-    style::{Print, Stylize},
+    style::{Print, ResetColor, Stylize},
     terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
+use std::collections::HashSet;
 use std::io::{self, Stdout, Write};
 
-struct StatsState {
-    config: Config,
+struct StatsState<'a> {
+    config: &'a mut Config,
     selected_mode: usize,
     view_mode: ViewMode,
+    recent_only: bool,
+    /// What the last action did, shown above the instructions.
+    status: Option<String>,
+    /// The summary card to print once the screen is closed, when it couldn't be copied.
+    card_to_print: Option<String>,
+    /// Highlighted row of the table, counted back from the newest result.
+    selected_result: usize,
+    replay: Option<Replay>,
 }
 
-enum ViewMode {
-    Table,
-    Graph,
+/// A saved test picked with Enter in the stats table, to be typed again with the same
+/// settings and words.
+pub struct Replay {
+    pub mode: ModeInfo,
+    pub seed: u64,
+    pub settings: config::WordSettings,
 }
 
-pub fn show_stats() -> io::Result<()> {
+/// What the stats screen was left with.
+pub struct Outcome {
+    /// The summary card, if one was asked for but couldn't be copied.
+    pub card: Option<String>,
+    pub replay: Option<Replay>,
+}
+
+/// A value plotted by `draw_graph`, and the top of its axis when that is fixed
+/// rather than taken from the data.
+struct Metric {
+    value: fn(&TestResult) -> f64,
+    axis_max: Option<f64>,
+}
+
+const WPM_METRIC: Metric = Metric { value: |r| r.wpm, axis_max: None };
+const ACCURACY_METRIC: Metric = Metric { value: |r| r.accuracy, axis_max: Some(100.0) };
+
+// Rows the split view needs for the graph before it is worth showing.
+const SPLIT_MIN_GRAPH_HEIGHT: u16 = 5;
+// Modes listed above the selected one before the list scrolls.
+const MAX_MODES_ABOVE_SELECTED: usize = 3;
+// Fewest results a mode needs before `--progress` fits a trend to them.
+const MIN_TREND_POINTS: usize = 3;
+// Latest results drawn in each sparkline of a `--report`.
+const SPARKLINE_POINTS: usize = 20;
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Shows the stats screen on its own, returning the test to replay if one was picked.
+/// The view is remembered only if `save` is set.
+pub fn show_stats(save: bool) -> error::Result<Option<Replay>> {
     let mut stdout = io::stdout();
-    stdout.execute(EnterAlternateScreen)?;
-    terminal::enable_raw_mode()?;
+    stdout.execute(EnterAlternateScreen).map_err(TypingTestError::Terminal)?;
+    terminal::enable_raw_mode().map_err(TypingTestError::Terminal)?;
 
+    let mut config = config::load_config_light();
+    let outcome = browse(&mut stdout, &mut config, save)?;
+
+    stdout.execute(ResetColor)?;
+    terminal::disable_raw_mode().map_err(TypingTestError::Terminal)?;
+    stdout.execute(LeaveAlternateScreen).map_err(TypingTestError::Terminal)?;
+    if let Some(card) = outcome.card {
+        println!("{}", card);
+    }
+    Ok(outcome.replay)
+}
+
+/// The stats screen itself, on a terminal that is already in raw mode on the alternate
+/// screen, which is left as it is. The view and selection are remembered in `config`,
+/// which is saved on the way out if `save` is set.
+pub fn browse(stdout: &mut Stdout, config: &mut Config, save: bool) -> error::Result<Outcome> {
+    let selected_mode = config
+        .stats_prefs
+        .selected_mode
+        .as_ref()
+        .and_then(|key| sorted_mode_keys(config).iter().position(|k| *k == key))
+        .unwrap_or(0);
     let mut state = StatsState {
-        config: config::load_config(),
-        selected_mode: 0,
-        view_mode: ViewMode::Table,
+        view_mode: config.stats_prefs.view,
+        recent_only: config.stats_prefs.recent_only,
+        selected_mode,
+        status: None,
+        card_to_print: None,
+        selected_result: 0,
+        replay: None,
+        config,
     };
 
     loop {
-        draw_stats(&mut stdout, &state)?;
+        draw_stats(stdout, &state)?;
 
         if let Event::Key(key_event) = event::read()? {
+            state.status = None;
             match key_event.code {
                 KeyCode::Char('q') => break,
+                KeyCode::Char('c') => {
+                    let card = summary_card(state.config);
+                    state.status = Some(match clipboard::copy(&card) {
+                        Ok(()) => "Summary card copied to the clipboard.".to_string(),
+                        Err(_) => {
+                            state.card_to_print = Some(card);
+                            "The summary card will be printed when you quit.".to_string()
+                        }
+                    });
+                }
                 KeyCode::Up => {
                     state.selected_mode = state.selected_mode.saturating_sub(1);
+                    state.selected_result = 0;
                 }
                 KeyCode::Down => {
                     let num_modes = state.config.results.len();
                     if num_modes > 0 {
                         state.selected_mode = (state.selected_mode + 1).min(num_modes - 1);
                     }
+                    state.selected_result = 0;
+                }
+                // The table rows go from newest to oldest, so Right means older.
+                KeyCode::Left if shows_table(state.view_mode) => {
+                    state.selected_result = state.selected_result.saturating_sub(1);
+                }
+                KeyCode::Right if shows_table(state.view_mode) => {
+                    let count = selected_results(&state).map_or(0, |results| results.len());
+                    state.selected_result = (state.selected_result + 1).min(count.saturating_sub(1));
+                }
+                KeyCode::Enter if shows_table(state.view_mode) => {
+                    let result = selected_results(&state)
+                        .and_then(|results| results.iter().rev().nth(state.selected_result));
+                    let replay = result.and_then(|result| {
+                        Some(Replay {
+                            mode: result.mode.clone()?,
+                            seed: result.seed?,
+                            settings: result.word_settings.clone()?,
+                        })
+                    });
+                    match replay {
+                        Some(replay) => {
+                            state.replay = Some(replay);
+                            break;
+                        }
+                        None => state.status = Some("Can't replay this result.".to_string()),
+                    }
                 }
                 KeyCode::Char('t') => state.view_mode = ViewMode::Table,
                 KeyCode::Char('g') => state.view_mode = ViewMode::Graph,
+                KeyCode::Char('s') => state.view_mode = ViewMode::Split,
+                KeyCode::Char('a') | KeyCode::Char('A') => state.view_mode = ViewMode::Accuracy,
+                KeyCode::Char('r') => state.recent_only = !state.recent_only,
+                KeyCode::Char('d') => state.config.daily_best_only = !state.config.daily_best_only,
+                KeyCode::Char('f') => {
+                    // Pinning moves the mode to the top, so keep it selected there.
+                    let keys = sorted_mode_keys(state.config);
+                    if let Some(key) = keys.get(state.selected_mode).map(|key| key.to_string()) {
+                        state.config.pinned_mode = if state.config.pinned_mode.as_ref() == Some(&key) {
+                            None
+                        } else {
+                            Some(key.clone())
+                        };
+                        state.selected_mode = mode_position(state.config, &key);
+                    }
+                }
+                KeyCode::Char('o') => {
+                    let selected = sorted_mode_keys(state.config)
+                        .get(state.selected_mode)
+                        .map(|key| key.to_string());
+                    state.config.stats_prefs.sort = state.config.stats_prefs.sort.next();
+                    if let Some(key) = selected {
+                        state.selected_mode = mode_position(state.config, &key);
+                    }
+                }
                 _ => {}
             }
         }
     }
 
-    terminal::disable_raw_mode()?;
-    stdout.execute(LeaveAlternateScreen)?;
-    Ok(())
+    state.config.stats_prefs = config::StatsPrefs {
+        view: state.view_mode,
+        recent_only: state.recent_only,
+        selected_mode: sorted_mode_keys(state.config)
+            .get(state.selected_mode)
+            .map(|key| key.to_string()),
+        sort: state.config.stats_prefs.sort,
+    };
+    // Failing to remember the view isn't worth an error on the way out.
+    if save {
+        config::save_config(state.config).ok();
+    }
+    Ok(Outcome { card: state.card_to_print, replay: state.replay })
+}
+
+/// Whether `view` includes the results table, where a result can be picked.
+fn shows_table(view: ViewMode) -> bool {
+    matches!(view, ViewMode::Table | ViewMode::Split)
+}
+
+/// The saved results of the mode selected in the list.
+fn selected_results<'a>(state: &'a StatsState) -> Option<&'a Vec<TestResult>> {
+    let key = *sorted_mode_keys(state.config).get(state.selected_mode)?;
+    state.config.results.get(key)
+}
+
+/// One line for shell prompts: best WPM over all modes, tests taken today and the
+/// current daily streak, as `best=<wpm> today=<n> streak=<days>`. A streak survives
+/// until the end of the day after the last practice.
+pub fn prompt_summary(config: &Config) -> String {
+    let results: Vec<&TestResult> = config.results.values().flatten().collect();
+    let best = results.iter().map(|r| r.wpm).fold(0.0, f64::max);
+    let days: Vec<NaiveDate> = results
+        .iter()
+        .filter_map(|r| NaiveDateTime::parse_from_str(&r.timestamp, "%Y-%m-%d %H:%M:%S").ok())
+        .map(|t| t.date())
+        .collect();
+    let today = Local::now().date_naive();
+    let today_count = days.iter().filter(|d| **d == today).count();
+
+    format!("best={:.1} today={} streak={}", best, today_count, streak(&results))
+}
+
+/// Days in a row with at least one test, counting back from today, or from yesterday
+/// if nothing has been done today yet.
+fn streak(results: &[&TestResult]) -> usize {
+    let dates: HashSet<NaiveDate> = results
+        .iter()
+        .filter_map(|r| NaiveDateTime::parse_from_str(&r.timestamp, "%Y-%m-%d %H:%M:%S").ok())
+        .map(|t| t.date())
+        .collect();
+    let today = Local::now().date_naive();
+    let mut day = if dates.contains(&today) { Some(today) } else { today.pred_opt() };
+    let mut streak = 0;
+    while let Some(d) = day.filter(|d| dates.contains(d)) {
+        streak += 1;
+        day = d.pred_opt();
+    }
+    streak
+}
+
+/// A few plain-text lines summing up all saved results, short enough to paste into a
+/// bio or a post.
+pub fn summary_card(config: &Config) -> String {
+    let results: Vec<&TestResult> = config.results.values().flatten().collect();
+    if results.is_empty() {
+        return "Typing Test: no results yet".to_string();
+    }
+    let counted: Vec<TestResult> = config.results.values().flat_map(|r| aggregated(config, r)).collect();
+    let count = counted.len() as f64;
+    let best = counted.iter().map(|r| r.wpm).fold(0.0, f64::max);
+    let average = counted.iter().map(|r| r.wpm).sum::<f64>() / count;
+    let accuracy = counted.iter().map(|r| r.accuracy).sum::<f64>() / count;
+    let streak = streak(&results);
+    format!(
+        "Typing Test: {} tests\nBest {} WPM | Avg {} WPM | {}% accuracy\nStreak: {} day{}",
+        results.len(),
+        ui::format_decimal(config, best, 1),
+        ui::format_decimal(config, average, 1),
+        ui::format_decimal(config, accuracy, 1),
+        streak,
+        if streak == 1 { "" } else { "s" }
+    )
+}
+
+/// A Markdown summary of every mode for `--report`: best, average and count, with a
+/// sparkline of recent WPM, in the order the stats screen lists them.
+pub fn markdown_report(config: &Config) -> String {
+    let mut lines = vec![
+        "# Typing Test Report".to_string(),
+        String::new(),
+        format!("Generated {}.", Local::now().format("%Y-%m-%d %H:%M")),
+        String::new(),
+    ];
+    let keys: Vec<&String> = sorted_mode_keys(config)
+        .into_iter()
+        .filter(|key| !config.results[*key].is_empty())
+        .collect();
+    if keys.is_empty() {
+        lines.push("No results saved yet.".to_string());
+        return lines.join("\n") + "\n";
+    }
+    lines.push("| Mode | Tests | Best WPM | Avg WPM | Avg accuracy | Recent WPM |".to_string());
+    lines.push("| --- | ---: | ---: | ---: | ---: | --- |".to_string());
+    for key in keys {
+        let results = &config.results[key];
+        let counted = aggregated(config, results);
+        let count = counted.len() as f64;
+        let best = counted.iter().map(|r| r.wpm).fold(0.0, f64::max);
+        let average = counted.iter().map(|r| r.wpm).sum::<f64>() / count;
+        let accuracy = counted.iter().map(|r| r.accuracy).sum::<f64>() / count;
+        lines.push(format!(
+            "| {} | {} | {} | {} | {}% | {} |",
+            mode_label(results, key).replace('|', "\\|"),
+            results.len(),
+            ui::format_decimal(config, best, 1),
+            ui::format_decimal(config, average, 1),
+            ui::format_decimal(config, accuracy, 1),
+            sparkline(&counted),
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// The WPM of the latest SPARKLINE_POINTS results as block characters, lowest to
+/// highest of those shown.
+fn sparkline(results: &[TestResult]) -> String {
+    let wpms: Vec<f64> = results.iter().rev().take(SPARKLINE_POINTS).rev().map(|r| r.wpm).collect();
+    let min = wpms.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = wpms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    wpms.iter()
+        .map(|wpm| {
+            let level = if max > min { (wpm - min) / (max - min) } else { 0.5 };
+            SPARKLINE_BARS[(level * (SPARKLINE_BARS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+/// One line per mode for `--progress`, or just the line for `mode` if given, saying
+/// how fast WPM has been changing per week.
+pub fn progress_lines(config: &Config, mode: Option<&str>) -> Vec<String> {
+    let keys: Vec<&String> = sorted_mode_keys(config)
+        .into_iter()
+        .filter(|key| mode.is_none_or(|mode| key.as_str() == mode))
+        .collect();
+    if keys.is_empty() {
+        return vec![match mode {
+            Some(mode) => format!("No results for mode '{}'.", mode),
+            None => "No stats saved yet.".to_string(),
+        }];
+    }
+    keys.into_iter()
+        .map(|key| {
+            let results = &aggregated(config, &config.results[key]);
+            match wpm_per_day(results) {
+                Some(rate) => {
+                    let weekly = rate * 7.0;
+                    let trend = if weekly.abs() < 0.05 {
+                        "steady".to_string()
+                    } else if weekly > 0.0 {
+                        format!("improving ~{} WPM/week", ui::format_decimal(config, weekly, 1))
+                    } else {
+                        format!("slowing ~{} WPM/week", ui::format_decimal(config, -weekly, 1))
+                    };
+                    format!("{}: {} over {} tests", key, trend, results.len())
+                }
+                None => format!(
+                    "{}: more data needed (at least {} tests, {} so far)",
+                    key,
+                    MIN_TREND_POINTS,
+                    results.len()
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Exponentially weighted moving average of WPM over `results`, oldest first: each
+/// result counts `alpha` (0 to 1) of the average and everything before it the rest, so
+/// the number follows current form more closely than a plain mean. 0 with no results.
+fn ewma(results: &[TestResult], alpha: f64) -> f64 {
+    let alpha = alpha.clamp(0.01, 1.0);
+    let mut wpms = results.iter().map(|r| r.wpm);
+    let first = wpms.next().unwrap_or(0.0);
+    wpms.fold(first, |average, wpm| alpha * wpm + (1.0 - alpha) * average)
+}
+
+/// The results averages, graphs and trends are computed from: all of them, or with
+/// `daily_best_only` just each day's best, so a long run of tired tests doesn't drag
+/// the picture down. Test counts and the table still show every result.
+fn aggregated(config: &Config, results: &[TestResult]) -> Vec<TestResult> {
+    if !config.daily_best_only {
+        return results.to_vec();
+    }
+    // Results are stored in time order, so each day's results are next to each other.
+    let mut best: Vec<TestResult> = Vec::new();
+    for result in results {
+        let day = result.timestamp.get(..10);
+        match best.last_mut() {
+            Some(last) if last.timestamp.get(..10) == day => {
+                if result.wpm > last.wpm {
+                    *last = result.clone();
+                }
+            }
+            _ => best.push(result.clone()),
+        }
+    }
+    best
+}
+
+/// Least-squares slope of WPM against time, in WPM per day. `None` with fewer than
+/// MIN_TREND_POINTS datable results or when they were all taken at the same moment.
+fn wpm_per_day(results: &[TestResult]) -> Option<f64> {
+    let points: Vec<(f64, f64)> = results
+        .iter()
+        .filter_map(|r| {
+            let time = NaiveDateTime::parse_from_str(&r.timestamp, "%Y-%m-%d %H:%M:%S").ok()?;
+            Some((time.and_utc().timestamp() as f64 / 86_400.0, r.wpm))
+        })
+        .collect();
+    if points.len() < MIN_TREND_POINTS {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    (variance > 0.0).then(|| covariance / variance)
 }
 
 fn draw_stats(stdout: &mut Stdout, state: &StatsState) -> io::Result<()> {
+    ui::apply_ui_colors(stdout, state.config)?;
     stdout.execute(Clear(ClearType::All))?;
     let (width, height) = terminal::size()?;
 
     let title = "Saved Stats";
-    let title_x = (width - title.len() as u16) / 2;
+    let title_x = ui::centered_x(width, title);
     stdout
         .execute(cursor::MoveTo(title_x, 1))?
         .execute(Print(title.bold()))?;
+    ui::apply_ui_colors(stdout, state.config)?;
 
-    let instructions = "Use ↑/↓ to select mode, 't' for table, 'g' for graph, 'q' to quit.";
-    let inst_x = (width - instructions.len() as u16) / 2;
+    let instructions = "Use ↑/↓ to select mode, 't' for table, 'g' for graph, 's' for both, 'a' for accuracy, 'r' for recent/all, 'd' for daily best, 'f' to pin, 'o' to sort, 'c' for a summary card, ←/→ and 'enter' to replay a result, 'q' to quit.";
+    let inst_x = ui::centered_x(width, instructions);
     stdout
-        .execute(cursor::MoveTo(inst_x, height - 2))?
+        .execute(cursor::MoveTo(inst_x, height.saturating_sub(2)))?
         .execute(Print(instructions.dark_grey()))?;
+    ui::apply_ui_colors(stdout, state.config)?;
+    if let Some(status) = &state.status {
+        stdout
+            .execute(cursor::MoveTo(ui::centered_x(width, status), height.saturating_sub(3)))?
+            .execute(Print(status))?;
+    }
 
     if state.config.results.is_empty() {
         let no_stats = "No stats saved yet.";
-        let no_stats_x = (width - no_stats.len() as u16) / 2;
+        let no_stats_x = ui::centered_x(width, no_stats);
         stdout
             .execute(cursor::MoveTo(no_stats_x, height / 2))?
             .execute(Print(no_stats))?;
         return stdout.flush();
     }
 
+    let mut sort_line = format!("Sorted by {}", state.config.stats_prefs.sort.label());
+    if state.config.daily_best_only {
+        sort_line.push_str(" | counting each day's best");
+    }
+    stdout
+        .execute(cursor::MoveTo(ui::centered_x(width, &sort_line), 2))?
+        .execute(Print(sort_line.dark_grey()))?;
+    ui::apply_ui_colors(stdout, state.config)?;
+
     let mut y = 4;
-    let mut mode_keys: Vec<_> = state.config.results.keys().collect();
-    mode_keys.sort();
+    let mode_keys = sorted_mode_keys(state.config);
+    let graph_width = width.saturating_sub(10);
 
-    for (i, key) in mode_keys.iter().enumerate() {
-        let display_key = key.replace("_", " ").to_uppercase();
+    // Scroll the list so the selected mode and its details stay on screen.
+    let first_visible = state.selected_mode.saturating_sub(MAX_MODES_ABOVE_SELECTED);
+    for (i, key) in mode_keys.iter().enumerate().skip(first_visible) {
+        if y + 3 > height {
+            break;
+        }
+        let count = state.config.results.get(*key).map_or(0, |results| results.len());
+        let label = mode_label(&state.config.results[*key], key);
+        let mut display_key = format!("{} ({})", label.to_uppercase(), count);
+        if state.config.pinned_mode.as_ref() == Some(*key) {
+            display_key.push_str(" [pinned]");
+        }
         if i == state.selected_mode {
             stdout
                 .execute(cursor::MoveTo(5, y))?
-                .execute(Print(display_key.negative()))?;
+                .execute(Print(display_key.as_str().negative()))?;
+            ui::apply_ui_colors(stdout, state.config)?;
+            let mode_results = aggregated(state.config, &state.config.results[*key]);
+            let average = mode_results.iter().map(|r| r.wpm).sum::<f64>() / mode_results.len().max(1) as f64;
+            let mut summary = format!("avg {} WPM", ui::format_decimal(state.config, average, 1));
+            if let Some(alpha) = state.config.recent_weighted_alpha {
+                let recent = ui::format_decimal(state.config, ewma(&mode_results, alpha), 1);
+                summary.push_str(&format!(" | recent-weighted avg {} WPM", recent));
+            }
+            stdout
+                .execute(cursor::MoveTo(5 + display_key.chars().count() as u16 + 2, y))?
+                .execute(Print(summary.dark_grey()))?;
+            ui::apply_ui_colors(stdout, state.config)?;
             y += 2;
+            let all_results = state.config.results.get(*key).unwrap();
+            let graphed = aggregated(state.config, all_results);
+            // The graph shows the oldest results that fit, or in recent mode the newest.
+            let results = if state.recent_only {
+                let count = state.config.graph_recent_count.min(graph_width as usize);
+                &graphed[graphed.len().saturating_sub(count)..]
+            } else {
+                &graphed[..]
+            };
+            // Rows left above the instructions line at the bottom.
+            let available = height.saturating_sub(y + 4);
             match state.view_mode {
                 ViewMode::Table => {
-                    y = draw_table(stdout, state.config.results.get(*key).unwrap(), y)?;
+                    y = draw_table(stdout, state.config, all_results, y, Some(state.selected_result))?;
                 }
                 ViewMode::Graph => {
-                    y = draw_graph(stdout, state.config.results.get(*key).unwrap(), y, width - 10)?;
+                    let graph_height = state.config.graph_height.min(available).max(2);
+                    y = draw_graph(stdout, state.config, results, y, graph_width, graph_height, &WPM_METRIC)?;
+                }
+                ViewMode::Accuracy => {
+                    let graph_height = state.config.graph_height.min(available).max(2);
+                    y = draw_graph(stdout, state.config, results, y, graph_width, graph_height, &ACCURACY_METRIC)?;
+                }
+                ViewMode::Split => {
+                    let table_rows = 1 + results.len().min(5) as u16;
+                    let enough_samples = results.len() >= state.config.graph_min_samples.max(2);
+                    if enough_samples && available >= SPLIT_MIN_GRAPH_HEIGHT + 2 + table_rows {
+                        let graph_height = state.config.graph_height.min(available - 2 - table_rows);
+                        y = draw_graph(stdout, state.config, results, y, graph_width, graph_height, &WPM_METRIC)?;
+                        ui::apply_ui_colors(stdout, state.config)?;
+                    }
+                    // Too short for both, or too few results: fall back to the table alone.
+                    y = draw_table(stdout, state.config, all_results, y, Some(state.selected_result))?;
                 }
             }
+            ui::apply_ui_colors(stdout, state.config)?;
         } else {
             stdout
                 .execute(cursor::MoveTo(5, y))?
@@ -112,7 +541,51 @@ fn draw_stats(stdout: &mut Stdout, state: &StatsState) -> io::Result<()> {
     stdout.flush()
 }
 
-fn draw_table(stdout: &mut Stdout, results: &[TestResult], start_y: u16) -> io::Result<u16> {
+/// Results keys in the order the stats screen lists them: the pinned mode first, then
+/// the rest by the chosen sort, with ties broken alphabetically.
+fn sorted_mode_keys(config: &Config) -> Vec<&String> {
+    let mut keys: Vec<_> = config.results.keys().collect();
+    keys.sort_by(|a, b| {
+        let pinned = |key: &String| config.pinned_mode.as_ref() != Some(key);
+        let (ra, rb) = (&config.results[*a], &config.results[*b]);
+        let best = |results: &[TestResult]| results.iter().map(|r| r.wpm).fold(0.0, f64::max);
+        // Timestamps are "%Y-%m-%d %H:%M:%S", so the latest sorts last as a string.
+        let latest = |results: &[TestResult]| results.iter().map(|r| r.timestamp.clone()).max();
+        let by_sort = match config.stats_prefs.sort {
+            ModeSort::Name => std::cmp::Ordering::Equal,
+            ModeSort::Recent => latest(rb).cmp(&latest(ra)),
+            ModeSort::Count => rb.len().cmp(&ra.len()),
+            ModeSort::BestWpm => best(rb).total_cmp(&best(ra)),
+        };
+        pinned(a).cmp(&pinned(b)).then(by_sort).then(a.cmp(b))
+    });
+    keys
+}
+
+/// How a mode is named on screen: the label of its latest described result, or the
+/// results key itself for results saved before modes were recorded.
+fn mode_label(results: &[TestResult], key: &str) -> String {
+    results
+        .iter()
+        .rev()
+        .find_map(|result| result.mode.as_ref())
+        .map_or_else(|| key.replace("_", " "), |mode| mode.label())
+}
+
+/// Index of `key` in the mode list, so the selection can follow it when the order changes.
+fn mode_position(config: &Config, key: &str) -> usize {
+    sorted_mode_keys(config).iter().position(|k| *k == key).unwrap_or(0)
+}
+
+/// The latest five results, or five around the `selected` one (counted back from the
+/// newest), which is highlighted.
+fn draw_table(
+    stdout: &mut Stdout,
+    config: &Config,
+    results: &[TestResult],
+    start_y: u16,
+    selected: Option<usize>,
+) -> io::Result<u16> {
     let mut y = start_y;
     let header = format!(
         "{: <25} | {: <10} | {: <10}",
@@ -121,35 +594,85 @@ fn draw_table(stdout: &mut Stdout, results: &[TestResult], start_y: u16) -> io::
     stdout
         .execute(cursor::MoveTo(7, y))?
         .execute(Print(header.bold()))?;
+    ui::apply_ui_colors(stdout, config)?;
     y += 1;
 
-    for result in results.iter().rev().take(5) {
+    let first = selected.map_or(0, |selected| selected.saturating_sub(4));
+    for (i, result) in results.iter().rev().enumerate().skip(first).take(5) {
         let line = format!(
-            "{: <25} | {: <10.2} | {: <9.2}%",
-            result.timestamp, result.wpm, result.accuracy
+            "{: <25} | {: <10} | {: <9}%",
+            result.timestamp,
+            ui::format_decimal(config, result.wpm, 2),
+            ui::format_decimal(config, result.accuracy, 2)
         );
-        stdout.execute(cursor::MoveTo(7, y))?.execute(Print(line))?;
+        stdout.execute(cursor::MoveTo(7, y))?;
+        if selected == Some(i) {
+            stdout.execute(Print(line.negative()))?;
+            ui::apply_ui_colors(stdout, config)?;
+        } else {
+            stdout.execute(Print(line))?;
+        }
         y += 1;
     }
     Ok(y)
 }
 
-fn draw_graph(stdout: &mut Stdout, results: &[TestResult], start_y: u16, width: u16) -> io::Result<u16> {
+/// Nearest-rank percentile (0–100) of `values`.
+fn percentile(values: &[f64], p: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn draw_graph(
+    stdout: &mut Stdout,
+    config: &Config,
+    results: &[TestResult],
+    start_y: u16,
+    width: u16,
+    graph_height: u16,
+    metric: &Metric,
+) -> io::Result<u16> {
     let y = start_y;
     if results.is_empty() {
-        return Ok(y);
+        stdout
+            .execute(cursor::MoveTo(7, y))?
+            .execute(Print("No results for this mode yet.".dark_grey()))?;
+        return Ok(y + 2);
+    }
+    // A line through a couple of points looks like a trend it isn't, so show the table.
+    let min_samples = config.graph_min_samples.max(2);
+    if results.len() < min_samples {
+        let message = format!(
+            "Collecting data: the graph needs {} tests, {} so far.",
+            min_samples,
+            results.len()
+        );
+        stdout
+            .execute(cursor::MoveTo(7, y))?
+            .execute(Print(message.dark_grey()))?;
+        ui::apply_ui_colors(stdout, config)?;
+        return draw_table(stdout, config, results, y + 2, None);
     }
 
-    let max_wpm = results.iter().map(|r| r.wpm).fold(0.0, f64::max);
-    let graph_height = 10;
+    // Scaling to a percentile keeps one freak result from flattening the rest; anything
+    // above it is drawn at the top.
+    let max_value = metric.axis_max.unwrap_or_else(|| {
+        let values: Vec<f64> = results.iter().map(metric.value).collect();
+        match config.graph_clip_percentile {
+            Some(p) => percentile(&values, p),
+            None => values.iter().cloned().fold(0.0, f64::max),
+        }
+    });
     let graph_width = width.min(results.len() as u16);
 
     let mut points: Vec<(u16, u16)> = Vec::new();
     if !results.is_empty() {
         for (i, result) in results.iter().enumerate().take(graph_width as usize) {
             let x = i as u16;
-            let y_pos = if max_wpm > 0.0 {
-                (result.wpm / max_wpm * (graph_height as f64)) as u16
+            let y_pos = if max_value > 0.0 {
+                (((metric.value)(result) / max_value).min(1.0) * (graph_height as f64)) as u16
             } else {
                 0
             };
@@ -186,16 +709,126 @@ fn draw_graph(stdout: &mut Stdout, results: &[TestResult], start_y: u16, width:
                  if points.contains(&(gx, gy)) {
                     stdout.execute(Print("*".red()))?;
                 } else {
-                    stdout.execute(Print(" "))?;
+                    // Skip rather than print so the UI background shows through.
+                    stdout.execute(cursor::MoveRight(1))?;
                 }
             }
         }
     }
     
     // Draw Y-axis labels
-    stdout.execute(cursor::MoveTo(2, y))?.execute(Print(format!("{:.0}", max_wpm)))?;
+    stdout.execute(cursor::MoveTo(2, y))?.execute(Print(format!("{:.0}", max_value)))?;
     stdout.execute(cursor::MoveTo(2, y + graph_height))?.execute(Print("0".to_string()))?;
 
 
     Ok(y + graph_height + 2)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(wpm: f64, timestamp: &str) -> TestResult {
+        TestResult {
+            wpm,
+            accuracy: 100.0,
+            max_combo: 0,
+            accuracy_mode: Default::default(),
+            mode: None,
+            seed: None,
+            word_settings: None,
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    #[test]
+    fn wpm_per_day_fits_the_trend() {
+        let results = [
+            result(50.0, "2026-01-01 12:00:00"),
+            result(52.0, "2026-01-03 12:00:00"),
+            result(54.0, "2026-01-05 12:00:00"),
+        ];
+        assert!((wpm_per_day(&results).unwrap() - 1.0).abs() < 1e-9);
+
+        let slowing = [
+            result(60.0, "2026-01-01 12:00:00"),
+            result(56.0, "2026-01-02 12:00:00"),
+            result(58.0, "2026-01-02 12:00:00"),
+        ];
+        assert!(wpm_per_day(&slowing).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn wpm_per_day_needs_enough_distinct_points() {
+        let two = [result(50.0, "2026-01-01 12:00:00"), result(60.0, "2026-01-02 12:00:00")];
+        assert_eq!(wpm_per_day(&two), None);
+
+        let same_moment = [
+            result(50.0, "2026-01-01 12:00:00"),
+            result(55.0, "2026-01-01 12:00:00"),
+            result(60.0, "2026-01-01 12:00:00"),
+        ];
+        assert_eq!(wpm_per_day(&same_moment), None);
+
+        // Results without a readable timestamp don't count towards the minimum.
+        let undated = [
+            result(50.0, "2026-01-01 12:00:00"),
+            result(55.0, "yesterday"),
+            result(60.0, "2026-01-02 12:00:00"),
+        ];
+        assert_eq!(wpm_per_day(&undated), None);
+    }
+
+    #[test]
+    fn ewma_follows_recent_results() {
+        let results = [
+            result(40.0, "2026-01-01 09:00:00"),
+            result(40.0, "2026-01-02 09:00:00"),
+            result(80.0, "2026-01-03 09:00:00"),
+        ];
+        // 0.5 * 80 + 0.5 * 40.
+        assert_eq!(ewma(&results, 0.5), 60.0);
+        // Weighted towards the latest result, it sits above the plain mean of 53.3.
+        assert!(ewma(&results, 0.5) > 160.0 / 3.0);
+        // Alpha 1 is just the latest result; alpha is kept above 0 so it never ignores it.
+        assert_eq!(ewma(&results, 1.0), 80.0);
+        assert!(ewma(&results, 0.0) > 40.0);
+    }
+
+    #[test]
+    fn ewma_of_no_or_one_result() {
+        assert_eq!(ewma(&[], 0.3), 0.0);
+        assert_eq!(ewma(&[result(55.0, "2026-01-01 09:00:00")], 0.3), 55.0);
+    }
+
+    #[test]
+    fn daily_best_only_keeps_each_days_best_result() {
+        let results = vec![
+            result(50.0, "2026-01-01 09:00:00"),
+            result(70.0, "2026-01-01 10:00:00"),
+            result(40.0, "2026-01-01 23:00:00"),
+            result(55.0, "2026-01-02 09:00:00"),
+        ];
+        let mut config = Config::default();
+        assert_eq!(aggregated(&config, &results).len(), 4);
+
+        config.daily_best_only = true;
+        let best: Vec<f64> = aggregated(&config, &results).iter().map(|r| r.wpm).collect();
+        assert_eq!(best, vec![70.0, 55.0]);
+    }
+
+    #[test]
+    fn daily_best_only_applies_to_the_report_and_summary_card() {
+        let mut config = Config::default();
+        config.results.insert(
+            "words_25_english".to_string(),
+            vec![result(40.0, "2026-01-01 09:00:00"), result(60.0, "2026-01-01 10:00:00")],
+        );
+        assert!(summary_card(&config).contains("Avg 50.0 WPM"));
+        assert!(markdown_report(&config).contains("| 2 | 60.0 | 50.0 |"));
+
+        config.daily_best_only = true;
+        // The test count stays, the average only counts the day's best.
+        assert!(summary_card(&config).contains("2 tests\nBest 60.0 WPM | Avg 60.0 WPM"));
+        assert!(markdown_report(&config).contains("| 2 | 60.0 | 60.0 |"));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::config::Config;
+use crate::frame::{Frame, Style};
+use crate::graphemes::graphemes;
+use crate::scoring::{self, DiffOp};
+use crossterm::{
+    cursor,
+    style::{Color, Print, SetBackgroundColor, SetForegroundColor},
+    terminal, ExecutableCommand, QueueableCommand,
+};
+use std::io::{self, Stdout, Write};
+
+/// Sets the configured UI foreground and background, falling back to the terminal's
+/// own defaults for any color that isn't configured.
+pub fn apply_ui_colors(stdout: &mut Stdout, config: &Config) -> io::Result<()> {
+    queue_ui_colors(stdout, config)?;
+    stdout.flush()
+}
+
+/// `apply_ui_colors` without flushing, for output that is written all at once.
+pub fn queue_ui_colors(stdout: &mut Stdout, config: &Config) -> io::Result<()> {
+    if !config.use_color {
+        return Ok(());
+    }
+    let fg = config.ui_fg.map_or(Color::Reset, Color::from);
+    let bg = config.ui_bg.map_or(Color::Reset, Color::from);
+    stdout
+        .queue(SetForegroundColor(fg))?
+        .queue(SetBackgroundColor(bg))?;
+    Ok(())
+}
+
+/// `value` with `precision` decimal places, written with the configured decimal
+/// separator. Only for display; saved results stay plain numbers.
+pub fn format_decimal(config: &Config, value: f64, precision: usize) -> String {
+    let text = format!("{:.*}", precision, value);
+    match config.decimal_separator {
+        '.' => text,
+        separator => text.replace('.', &separator.to_string()),
+    }
+}
+
+/// Column at which `text` starts when centered, clamped to the left edge when it
+/// doesn't fit.
+pub fn centered_x(width: u16, text: &str) -> u16 {
+    width.saturating_sub(text.chars().count() as u16) / 2
+}
+
+/// A line of text for `draw_centered_lines`, made of spans that may each have their
+/// own color.
+pub struct Line {
+    pub spans: Vec<(String, Option<Color>)>,
+}
+
+impl Line {
+    pub fn colored(text: String, color: Color) -> Self {
+        Self { spans: vec![(text, Some(color))] }
+    }
+
+    fn text(&self) -> String {
+        self.spans.iter().map(|(text, _)| text.as_str()).collect()
+    }
+}
+
+impl From<String> for Line {
+    fn from(text: String) -> Self {
+        Self { spans: vec![(text, None)] }
+    }
+}
+
+/// Draws each line centered horizontally, starting from the middle row of the terminal.
+pub fn draw_centered_lines(stdout: &mut Stdout, config: &Config, lines: &[Line]) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    for (i, line) in lines.iter().enumerate() {
+        let x = centered_x(width, &line.text());
+        let y = (height / 2) + i as u16;
+        stdout.execute(cursor::MoveTo(x, y))?;
+        for (text, color) in &line.spans {
+            if let Some(color) = color.filter(|_| config.use_color) {
+                stdout.execute(SetForegroundColor(color))?;
+            }
+            stdout.execute(Print(text))?;
+            apply_ui_colors(stdout, config)?;
+        }
+    }
+    Ok(())
+}
+
+/// Maps `fraction` (0 to 1) onto a red → yellow → green gradient.
+pub fn gradient_color(fraction: f64) -> Color {
+    let f = fraction.clamp(0.0, 1.0);
+    let (r, g) = if f < 0.5 {
+        (255.0, 510.0 * f)
+    } else {
+        (510.0 * (1.0 - f), 255.0)
+    };
+    Color::Rgb { r: r as u8, g: g as u8, b: 0 }
+}
+
+// Color of every word but the current one in focus mode.
+const FOCUS_DIM_COLOR: Color = Color::Rgb { r: 60, g: 60, b: 60 };
+
+/// Draws one test word into `frame` at `(x, y)`, colored by what has been typed for it. The current
+/// word also shows extra typed characters past its end; the others show untyped
+/// characters in grey, or are all dimmed in focus mode. The character at `ghost`, if
+/// any, is underlined to mark the pace runner. Without color support, mistyped
+/// characters are underlined instead of colored.
+pub fn draw_word(
+    frame: &mut Frame,
+    config: &Config,
+    word: &str,
+    typed: &str,
+    is_current: bool,
+    (x, y): (u16, u16),
+    ghost: Option<usize>,
+) {
+    let theme = &config.color_theme;
+    let word = graphemes(word);
+    let typed = graphemes(typed);
+    let alignment = scoring::optional_alignment(&typed, &word, config.case_insensitive, &config.optional_chars);
+
+    for (i, (target, aligned)) in word.iter().zip(alignment.iter()).enumerate() {
+        let color = match aligned.map(|j| typed.get(j)) {
+            _ if config.focus_mode && !is_current => FOCUS_DIM_COLOR,
+            // An optional character that was left out.
+            None => Color::from(theme.correct),
+            Some(typed) => match typed {
+                Some(typed) if scoring::chars_match(typed, target, config.case_insensitive) => {
+                    Color::from(theme.correct)
+                }
+                Some(_) => Color::from(theme.incorrect),
+                None if is_current => Color::from(theme.default),
+                None => Color::DarkGrey,
+            },
+        };
+        let mistyped = matches!(aligned.map(|j| typed.get(j)), Some(Some(typed))
+            if !scoring::chars_match(typed, target, config.case_insensitive));
+        let style = Style {
+            fg: Some(color),
+            underlined: ghost == Some(i) || (mistyped && !config.use_color),
+        };
+        frame.print(x + i as u16, y, target, style);
+    }
+    // Typed characters beyond the ones lined up with the target are extras.
+    let expected = alignment.iter().filter(|aligned| aligned.is_some()).count();
+    if is_current && typed.len() > expected {
+        let style = Style { fg: Some(Color::from(theme.incorrect)), underlined: !config.use_color };
+        for (i, extra) in typed.iter().skip(expected).enumerate() {
+            frame.print(x + (word.len() + i) as u16, y, extra, style);
+        }
+    }
+}
+
+/// Column of the caret within the current word, just after the last typed character.
+/// Optional characters that were left out still take up their column.
+pub fn caret_offset(config: &Config, word: &str, typed: &str) -> u16 {
+    let word = graphemes(word);
+    let typed = graphemes(typed);
+    let alignment = scoring::optional_alignment(&typed, &word, config.case_insensitive, &config.optional_chars);
+    let expected = alignment.iter().filter(|aligned| aligned.is_some()).count();
+    let covered = alignment
+        .iter()
+        .take_while(|aligned| aligned.is_none_or(|j| j < typed.len()))
+        .count();
+    (covered + typed.len().saturating_sub(expected)) as u16
+}
+
+/// Spells out a word alignment for the results review: `[x>y]` for typing x instead of
+/// y, `[+x]` for an extra x and `[-y]` for a missed y.
+pub fn format_diff(ops: &[DiffOp]) -> String {
+    ops.iter()
+        .map(|op| match op {
+            DiffOp::Match(c) => c.to_string(),
+            DiffOp::Substitute { typed, target } => format!("[{}>{}]", typed, target),
+            DiffOp::Insert(c) => format!("[+{}]", c),
+            DiffOp::Delete(c) => format!("[-{}]", c),
+        })
+        .collect()
+}
+
+/// `format_diff` as a line for the results screen, with matched characters and mistakes
+/// in the review colors.
+pub fn diff_line(config: &Config, target: &str, ops: &[DiffOp]) -> Line {
+    let theme = config.review_color_theme.as_ref().unwrap_or(&config.color_theme);
+    let mut spans = vec![(format!("{}: ", target), None)];
+    spans.extend(ops.iter().map(|op| {
+        let color = match op {
+            DiffOp::Match(_) => theme.correct,
+            _ => theme.incorrect,
+        };
+        (format_diff(std::slice::from_ref(op)), Some(Color::from(color)))
+    }));
+    Line { spans }
+}
+
+/// Renders a horizontal bar `width` cells wide, filled to `fraction` (clamped to 0..=1)
+/// with eighth-block precision.
+pub fn render_bar(fraction: f64, width: u16) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let width = width as usize;
+    let eighths = (fraction.clamp(0.0, 1.0) * width as f64 * 8.0).round() as usize;
+    let full = eighths / 8;
+
+    let mut bar = "█".repeat(full);
+    if full < width {
+        bar.push(PARTIAL[eighths % 8]);
+        bar.push_str(&" ".repeat(width - full - 1));
+    }
+    bar
+}
@@ -0,0 +1,242 @@
+use crate::config::{LanguagePack, MIXED_LANGUAGE};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+// Approximate occurrences per million words of running English text for the most
+// common words, used when a language pack doesn't provide its own frequencies.
+const ENGLISH_FREQUENCIES: &[(&str, f64)] = &[
+    ("the", 50000.0), ("of", 25000.0), ("and", 24000.0), ("to", 23000.0), ("a", 21000.0),
+    ("in", 17000.0), ("is", 10000.0), ("that", 9500.0), ("you", 9000.0), ("it", 9000.0),
+    ("i", 8000.0), ("was", 7500.0), ("for", 7500.0), ("he", 7000.0), ("on", 6500.0),
+    ("with", 6000.0), ("be", 5500.0), ("as", 5000.0), ("this", 4800.0), ("are", 4500.0),
+    ("his", 4500.0), ("they", 4500.0), ("at", 4500.0), ("have", 4500.0), ("not", 4000.0),
+    ("from", 3800.0), ("we", 3700.0), ("or", 3300.0), ("by", 3300.0), ("what", 3300.0),
+    ("had", 3000.0), ("but", 3000.0), ("one", 2900.0), ("an", 2900.0), ("were", 2800.0),
+    ("all", 2700.0), ("she", 2600.0), ("do", 2500.0), ("there", 2400.0), ("their", 2400.0),
+    ("will", 2400.0), ("so", 2400.0), ("when", 2300.0), ("if", 2300.0), ("her", 2300.0),
+    ("your", 2200.0), ("can", 2200.0), ("would", 2200.0), ("said", 2000.0), ("which", 2000.0),
+    ("up", 2000.0), ("about", 2000.0), ("my", 2000.0), ("out", 1800.0), ("like", 1800.0),
+    ("has", 1800.0), ("more", 1800.0), ("get", 1800.0), ("who", 1700.0), ("time", 1600.0),
+    ("how", 1500.0), ("them", 1500.0), ("some", 1500.0), ("him", 1500.0), ("into", 1500.0),
+    ("no", 1500.0), ("could", 1500.0), ("did", 1500.0), ("been", 1400.0), ("other", 1300.0),
+    ("then", 1300.0), ("make", 1300.0), ("go", 1300.0), ("people", 1300.0), ("now", 1300.0),
+    ("these", 1200.0), ("than", 1200.0), ("see", 1100.0), ("its", 1100.0), ("two", 1000.0),
+    ("first", 1000.0), ("use", 1000.0), ("many", 900.0), ("way", 900.0), ("down", 900.0),
+    ("come", 900.0), ("may", 900.0), ("each", 800.0), ("made", 800.0), ("look", 700.0),
+    ("long", 700.0), ("day", 700.0), ("find", 600.0), ("number", 500.0), ("part", 500.0),
+    ("water", 400.0), ("call", 400.0), ("write", 300.0),
+];
+// Weight given to words missing from the table above.
+const UNLISTED_FREQUENCY: f64 = 20.0;
+
+/// Picks a weight for each word in `list`: the pack's own frequencies when it has one
+/// per word, otherwise the embedded English table.
+pub fn frequency_weights(list: &[String], pack_frequencies: Option<&[f64]>) -> Vec<f64> {
+    if let Some(frequencies) = pack_frequencies
+        && frequencies.len() == list.len()
+    {
+        return frequencies.to_vec();
+    }
+
+    let table: HashMap<&str, f64> = ENGLISH_FREQUENCIES.iter().cloned().collect();
+    list.iter()
+        .map(|word| {
+            table
+                .get(word.to_lowercase().as_str())
+                .copied()
+                .unwrap_or(UNLISTED_FREQUENCY)
+        })
+        .collect()
+}
+
+/// Draws test words from a list, either uniformly (see `extend_word_pool`) or in
+/// proportion to per-word weights.
+pub struct WordSampler<'a> {
+    list: &'a [String],
+    weights: Option<WeightedIndex<f64>>,
+    /// What's left of the current shuffle of `list`, last word first, so a top-up
+    /// carries on where the previous one stopped.
+    round: Vec<&'a String>,
+}
+
+impl<'a> WordSampler<'a> {
+    /// Unusable weights (wrong length, all zero, negative) fall back to uniform sampling.
+    pub fn new(list: &'a [String], weights: Option<Vec<f64>>) -> Self {
+        let weights = weights
+            .filter(|w| w.len() == list.len())
+            .and_then(|w| WeightedIndex::new(w).ok());
+        Self { list, weights, round: Vec::new() }
+    }
+
+    pub fn build<R: Rng>(&mut self, count: usize, rng: &mut R) -> Vec<String> {
+        let mut pool = Vec::with_capacity(count);
+        self.extend(&mut pool, count, rng);
+        pool
+    }
+
+    pub fn extend<R: Rng>(&mut self, pool: &mut Vec<String>, count: usize, rng: &mut R) {
+        let Some(weights) = &self.weights else {
+            extend_word_pool(pool, self.list, &mut self.round, count, rng);
+            return;
+        };
+
+        for _ in 0..count {
+            let mut word = &self.list[weights.sample(rng)];
+            // Common words come up often; re-draw a few times to avoid back-to-back repeats.
+            for _ in 0..3 {
+                if pool.last() != Some(word) {
+                    break;
+                }
+                word = &self.list[weights.sample(rng)];
+            }
+            pool.push(word.clone());
+        }
+    }
+}
+
+/// A checksum of a word list and its frequencies, stable across runs and builds, to
+/// tell whether a saved test's words came from the list as it is now.
+pub fn fingerprint(list: &[String], frequencies: Option<&[f64]>) -> u64 {
+    // FNV-1a, with a byte no UTF-8 text contains between words.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut add = |bytes: &[u8]| {
+        for byte in bytes {
+            hash = (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for word in list {
+        add(word.as_bytes());
+        add(&[0xff]);
+    }
+    for frequency in frequencies.unwrap_or_default() {
+        add(&frequency.to_bits().to_le_bytes());
+    }
+    hash
+}
+
+/// The fingerprint of the list a test in `language` draws from: every pack's words for
+/// the mixed source, otherwise the pack's own words and frequencies.
+pub fn language_fingerprint(packs: &[LanguagePack], language: &str) -> Option<u64> {
+    if language == MIXED_LANGUAGE {
+        return Some(fingerprint(&mixed_word_list(packs), None));
+    }
+    let pack = packs.iter().find(|pack| pack.name == language)?;
+    Some(fingerprint(&pack.words, pack.frequencies.as_deref()))
+}
+
+/// Combines the words of every pack, dropping duplicates shared between packs.
+pub fn mixed_word_list(packs: &[LanguagePack]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    packs
+        .iter()
+        .flat_map(|pack| pack.words.iter())
+        .filter(|word| seen.insert(word.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Splits hyphenated words like "well-being" into their parts, each keeping the
+/// frequency of the word it came from, if there are frequencies.
+pub fn split_hyphenated(list: &[String], frequencies: Option<&[f64]>) -> (Vec<String>, Option<Vec<f64>>) {
+    let frequencies = frequencies.filter(|f| f.len() == list.len());
+    let mut words = Vec::new();
+    let mut weights = Vec::new();
+    for (i, word) in list.iter().enumerate() {
+        for part in word.split('-').filter(|part| !part.is_empty()) {
+            words.push(part.to_string());
+            if let Some(frequencies) = frequencies {
+                weights.push(frequencies[i]);
+            }
+        }
+    }
+    (words, frequencies.map(|_| weights))
+}
+
+/// The words of `list` that `keep` accepts, with their frequencies.
+pub fn filter(
+    list: &[String],
+    frequencies: Option<&[f64]>,
+    keep: impl Fn(&str) -> bool,
+) -> (Vec<String>, Option<Vec<f64>>) {
+    let frequencies = frequencies.filter(|f| f.len() == list.len());
+    let kept: Vec<usize> = (0..list.len()).filter(|&i| keep(&list[i])).collect();
+    (
+        kept.iter().map(|&i| list[i].clone()).collect(),
+        frequencies.map(|f| kept.iter().map(|&i| f[i]).collect()),
+    )
+}
+
+/// Appends `count` words to `pool` by walking through shuffled copies of `list`, so
+/// every word is used once before any word repeats. `round` holds the rest of the
+/// current shuffle between calls.
+fn extend_word_pool<'a, R: Rng>(
+    pool: &mut Vec<String>,
+    list: &'a [String],
+    round: &mut Vec<&'a String>,
+    count: usize,
+    rng: &mut R,
+) {
+    if list.is_empty() {
+        return;
+    }
+
+    for _ in 0..count {
+        if round.is_empty() {
+            round.extend(list);
+            round.shuffle(rng);
+            // Don't repeat the previous word across the seam between two shuffles.
+            if round.len() > 1 && pool.last() == round.first().copied() {
+                let last = round.len() - 1;
+                round.swap(0, last);
+            }
+            round.reverse();
+        }
+        pool.extend(round.pop().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn list(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("word{}", i)).collect()
+    }
+
+    #[test]
+    fn top_ups_use_every_word_once_before_repeating() {
+        let list = list(30);
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut sampler = WordSampler::new(&list, None);
+        // A time-mode test: an initial pool, then top-ups of 20 words.
+        let mut pool = sampler.build(25, &mut rng);
+        for _ in 0..5 {
+            sampler.extend(&mut pool, 20, &mut rng);
+        }
+
+        assert_eq!(pool.len(), 125);
+        for round in pool.chunks(list.len()) {
+            let distinct: HashSet<&String> = round.iter().collect();
+            assert_eq!(distinct.len(), round.len(), "a word repeated within one shuffle");
+        }
+        assert!(pool.windows(2).all(|pair| pair[0] != pair[1]), "a word was repeated back to back");
+    }
+
+    #[test]
+    fn pool_from_a_short_list_has_no_back_to_back_repeats() {
+        let list = list(3);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut sampler = WordSampler::new(&list, None);
+        let mut pool = Vec::new();
+        for _ in 0..50 {
+            sampler.extend(&mut pool, 7, &mut rng);
+        }
+
+        assert_eq!(pool.len(), 350);
+        assert!(pool.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::config::{self, LanguagePack};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Fetches a word list from `url`, falling back to the last cached copy when the
+/// download fails. The list may be a JSON array of words, a language pack, or plain
+/// text with one word per line.
+pub fn load_words(url: &str) -> io::Result<Vec<String>> {
+    let cache_path = cache_path(url);
+    match fetch(url).and_then(|body| parse_words(&body)) {
+        Ok(words) => {
+            if let Some(path) = &cache_path {
+                fs::write(path, words.join("\n")).ok();
+            }
+            Ok(words)
+        }
+        Err(e) => match cache_path.and_then(|path| fs::read_to_string(path).ok()) {
+            Some(cached) => {
+                eprintln!("Could not download {} ({}), using the cached copy.", url, e);
+                parse_words(&cached)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+#[cfg(feature = "words-url")]
+fn fetch(url: &str) -> io::Result<String> {
+    let output = std::process::Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", "--max-time", "10", url])
+        .output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(message));
+    }
+    String::from_utf8(output.stdout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(not(feature = "words-url"))]
+fn fetch(_url: &str) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "this build doesn't support --words-url; rebuild with `--features words-url`",
+    ))
+}
+
+fn parse_words(body: &str) -> io::Result<Vec<String>> {
+    let words = if let Ok(words) = serde_json::from_str::<Vec<String>>(body) {
+        words
+    } else if let Ok(pack) = serde_json::from_str::<LanguagePack>(body) {
+        pack.words
+    } else {
+        body.lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+
+    if words.is_empty() {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "the word list is empty"))
+    } else {
+        Ok(words)
+    }
+}
+
+fn cache_path(url: &str) -> Option<PathBuf> {
+    let dir = config::get_config_dir()?.join("url_cache");
+    fs::create_dir_all(&dir).ok()?;
+    let name: String = url
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    Some(dir.join(format!("{}.txt", name)))
+}